@@ -0,0 +1,474 @@
+//! Checking, constructing and enumerating adjustment sets.
+//!
+//! DAGs are handled as CPDAGs without undirected edges. Adjustment sets can
+//! additionally be enumerated in ADMGs.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::{
+    check_costs, orient_chordal, reach_set, separators, to_set, EdgeLists, MinimalSeparators,
+};
+use crate::{
+    instance::{Graph, Sets},
+    reach::{reach, Settings},
+    ruletable::Ruletable,
+    ParseGraphError,
+};
+
+/// Checks whether `w` is a valid adjustment set relative to `x` and `y` in a CPDAG.
+///
+/// Follows the generalized adjustment criterion of Perković et al. (2018): the
+/// effect has to be amenable, `w` may not contain forbidden nodes and all proper
+/// non-causal paths from `x` to `y` have to be blocked by `w`.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->` and `---`.
+pub fn is_cpdag_adjustment(
+    cpdag: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    w: &[usize],
+) -> Result<bool, ParseGraphError> {
+    let (x, y, w) = (to_set(x), to_set(y), to_set(w));
+    let not_amenable_table = Ruletable::not_amenable_cpdag();
+    let possible_descendants_table = Ruletable::possible_descendants_cpdag();
+    let graph = Graph::new(cpdag, &not_amenable_table)?;
+
+    let not_amenable = reach_set(&graph, &not_amenable_table, &[("X", &x)]);
+    let causal_nodes = causal_nodes(&graph, &x, &y);
+    let forbidden = reach_set(&graph, &possible_descendants_table, &[("X", &causal_nodes)]);
+    let backdoor_connected = reach_set(
+        &graph,
+        &Ruletable::backdoor_connected_cpdag(),
+        &[("X", &x), ("C", &causal_nodes), ("W", &w)],
+    );
+
+    Ok(not_amenable.is_disjoint(&y)
+        && forbidden.is_disjoint(&w)
+        && backdoor_connected.is_disjoint(&y))
+}
+
+/// Constructs the optimal adjustment set relative to `x` and `y` in a CPDAG.
+///
+/// Returns `None` if there is no valid adjustment set, that is, if not all nodes
+/// in `y` are possible descendants of `x` or if the effect is not amenable or
+/// not identifiable by adjustment. Otherwise the optimal adjustment set of
+/// Henckel et al. (2022) is returned in ascending order.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->` and `---`.
+pub fn optimal_adjustment(
+    cpdag: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+) -> Result<Option<Vec<usize>>, ParseGraphError> {
+    let (x, y) = (to_set(x), to_set(y));
+    let possible_descendants_table = Ruletable::possible_descendants_cpdag();
+    let graph = Graph::new(cpdag, &possible_descendants_table)?;
+
+    let descendants = reach_set(&graph, &possible_descendants_table, &[("X", &x)]);
+    if !y.is_subset(&descendants) {
+        return Ok(None);
+    }
+
+    let not_amenable = reach_set(&graph, &Ruletable::not_amenable_cpdag(), &[("X", &x)]);
+    let causal_nodes = causal_nodes(&graph, &x, &y);
+    let forbidden = reach_set(&graph, &possible_descendants_table, &[("X", &causal_nodes)]);
+    if !forbidden.is_disjoint(&x) || !not_amenable.is_disjoint(&y) {
+        return Ok(None);
+    }
+
+    let parents = reach_set(&graph, &Ruletable::parents_cpdag(), &[("X", &causal_nodes)]);
+    Ok(Some(
+        parents
+            .into_iter()
+            .filter(|v| !forbidden.contains(v) && !x.contains(v))
+            .collect(),
+    ))
+}
+
+/// Computes the parent adjustment distance between two CPDAGs over `num_nodes` nodes.
+///
+/// Counts the ordered pairs of nodes `(x, y)` for which the parents of `x` in
+/// `guess`, or the non-amenability of `y` in `guess`, lead to a wrong conclusion
+/// about the effect of `x` on `y` in `truth`, following Henckel et al. (2024).
+///
+/// # Errors
+/// Returns an error if one of the graphs contains edge types other than `-->` and `---`
+/// or an edge with an endpoint that is not smaller than `num_nodes`.
+pub fn parent_aid(
+    num_nodes: usize,
+    truth: &EdgeLists,
+    guess: &EdgeLists,
+) -> Result<usize, ParseGraphError> {
+    let possible_descendants_table = Ruletable::possible_descendants_cpdag();
+    let not_amenable_table = Ruletable::not_amenable_cpdag();
+    let forbidden_path_table = Ruletable::forbidden_path_connected_cpdag();
+    let non_causal_table = Ruletable::non_causal_connected_cpdag();
+    let graph_truth = Graph::new(truth, &possible_descendants_table)?;
+    let graph_guess = Graph::new(guess, &possible_descendants_table)?;
+    for (name, edge_lists) in [("truth", truth), ("guess", guess)] {
+        if let Some((u, v)) = edge_lists
+            .values()
+            .flatten()
+            .find(|&&(u, v)| u >= num_nodes || v >= num_nodes)
+        {
+            return Err(ParseGraphError(format!(
+                "edge ({u}, {v}) of {name} has an endpoint outside of the {num_nodes} nodes"
+            )));
+        }
+    }
+
+    let mut parents = vec![BTreeSet::new(); num_nodes];
+    for &(u, v) in guess.get("-->").into_iter().flatten() {
+        parents[v].insert(u);
+    }
+
+    let mut mistakes = 0;
+    for (x, pa) in parents.iter().enumerate() {
+        let xs = to_set(&[x]);
+        let not_amenable_guess = reach_set(&graph_guess, &not_amenable_table, &[("X", &xs)]);
+        let descendants_truth = reach_set(&graph_truth, &possible_descendants_table, &[("X", &xs)]);
+        let not_amenable_truth = reach_set(&graph_truth, &not_amenable_table, &[("X", &xs)]);
+        let mut not_adjustment_truth = not_amenable_truth.clone();
+        not_adjustment_truth.extend(reach_set(
+            &graph_truth,
+            &forbidden_path_table,
+            &[("X", &xs), ("W", pa)],
+        ));
+        not_adjustment_truth.extend(reach_set(
+            &graph_truth,
+            &non_causal_table,
+            &[("X", &xs), ("W", pa)],
+        ));
+
+        for y in (0..num_nodes).filter(|&y| y != x) {
+            let mistake = if pa.contains(&y) {
+                descendants_truth.contains(&y)
+            } else if not_amenable_guess.contains(&y) {
+                !not_amenable_truth.contains(&y)
+            } else {
+                not_adjustment_truth.contains(&y)
+            };
+            if mistake {
+                mistakes += 1;
+            }
+        }
+    }
+    Ok(mistakes)
+}
+
+// nodes on proper possibly causal paths from x to y
+fn causal_nodes(graph: &Graph, x: &BTreeSet<usize>, y: &BTreeSet<usize>) -> BTreeSet<usize> {
+    let ancestors = reach_set(
+        graph,
+        &Ruletable::possible_ancestors_cpdag(),
+        &[("X", y), ("W", x)],
+    );
+    let descendants = reach_set(graph, &Ruletable::possible_descendants_cpdag(), &[("X", x)]);
+    ancestors.intersection(&descendants).copied().collect()
+}
+
+/// Options restricting the adjustment sets listed by [`enumerate_adjustment_sets`].
+#[derive(Debug, Default, Clone)]
+pub struct AdjustmentOptions {
+    /// Lists only adjustment sets of which no proper subset containing `required` is an adjustment set.
+    pub minimal: bool,
+    /// Lists only adjustment sets with at most this many nodes.
+    pub max_size: Option<usize>,
+    /// Nodes that every listed adjustment set has to contain.
+    pub required: Vec<usize>,
+    /// Nodes that no listed adjustment set may contain.
+    pub forbidden: Vec<usize>,
+}
+
+/// Enumerates the adjustment sets relative to `x` and `y` in a DAG, CPDAG or ADMG.
+///
+/// The graph is treated as an ADMG if it has bidirected edges `<->` and as a
+/// CPDAG otherwise. In a CPDAG in which the effect is not amenable, there are no
+/// adjustment sets. The candidate nodes range up to the largest node in the
+/// graph, `x`, `y` and `options.required`. The sets are listed lazily, each in
+/// ascending order, and every set is listed exactly once.
+///
+/// The adjustment sets are the d-separators of `x` and `y` in the proper
+/// backdoor graph that contain no forbidden nodes, where CPDAGs are reduced to
+/// an arbitrary DAG in their equivalence class (van der Zander et al., 2019).
+/// All valid adjustment sets are listed by deciding for one node after the other
+/// whether it is in the set, only descending into branches that still contain
+/// an adjustment set. Minimal adjustment sets are listed as minimal separators
+/// with [`enumerate_minimal_separators`](super::enumerate_minimal_separators).
+/// Both run with polynomial delay unless `max_size` is set, in which case
+/// branches containing only larger sets may be explored before they are
+/// discarded.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->` and `---`,
+/// or `-->` and `<->`.
+pub fn enumerate_adjustment_sets(
+    graph: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    options: &AdjustmentOptions,
+) -> Result<AdjustmentSets, ParseGraphError> {
+    let (x, y) = (to_set(x), to_set(y));
+    let required = to_set(&options.required);
+    let max_size = options.max_size.unwrap_or(usize::MAX);
+    let (backdoor_graph, mut allowed) = match backdoor_instance(graph, &x, &y, &required)? {
+        Some(instance) => instance,
+        None => return Ok(AdjustmentSets::empty()),
+    };
+    for v in options.forbidden.iter() {
+        allowed.remove(v);
+    }
+    if !required.is_subset(&allowed) || required.len() > max_size {
+        return Ok(AdjustmentSets::empty());
+    }
+
+    if options.minimal {
+        let separators = separators::enumerate_minimal_separators(
+            &backdoor_graph,
+            &x.into_iter().collect::<Vec<_>>(),
+            &y.into_iter().collect::<Vec<_>>(),
+            &options.required,
+            &allowed.into_iter().collect::<Vec<_>>(),
+        )?;
+        return Ok(AdjustmentSets(Inner::Minimal(
+            Box::new(separators),
+            max_size,
+        )));
+    }
+
+    let dconnected_table = Ruletable::dconnected_admg();
+    let sets = HashMap::from([("X".to_owned(), x.iter().copied().collect())]);
+    let mut search = ValidSearch {
+        graph: Graph::new(&backdoor_graph, &dconnected_table)?,
+        sets: Sets::new(&sets, &dconnected_table).expect("X is declared in rule table"),
+        z_id: dconnected_table
+            .get_set_id("Z")
+            .expect("Z is declared in rule table"),
+        z: BTreeSet::new(),
+        dconnected_table,
+        ancestors_table: Ruletable::ancestors_admg(),
+        x,
+        y,
+        max_size,
+        stack: Vec::new(),
+    };
+    if search.feasible(&required, &allowed) {
+        search.stack.push((required, allowed));
+    }
+    Ok(AdjustmentSets(Inner::Valid(Box::new(search))))
+}
+
+/// Finds an adjustment set relative to `x` and `y` of minimum total cost in a DAG, CPDAG or ADMG.
+///
+/// Node `v` has cost `costs[v]`, or one if there is no such entry, so passing no
+/// costs yields an adjustment set of minimum size. Nodes with infinite cost are
+/// never in the set. Returns the set in ascending order together with its cost,
+/// or `None` if there is no valid adjustment set. The graph is handled as in
+/// [`enumerate_adjustment_sets`], and the set is a minimum-cost separator in the
+/// proper backdoor graph found by
+/// [`find_min_cost_dsep`](super::find_min_cost_dsep).
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->` and `---`,
+/// or `-->` and `<->`, or if a cost is negative or not a number.
+pub fn min_cost_adjustment(
+    graph: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    costs: &[f64],
+) -> Result<Option<(Vec<usize>, f64)>, ParseGraphError> {
+    check_costs(costs)?;
+    let (x, y) = (to_set(x), to_set(y));
+    let (backdoor_graph, allowed) = match backdoor_instance(graph, &x, &y, &BTreeSet::new())? {
+        Some(instance) => instance,
+        None => return Ok(None),
+    };
+    separators::find_min_cost_dsep(
+        &backdoor_graph,
+        &x.into_iter().collect::<Vec<_>>(),
+        &y.into_iter().collect::<Vec<_>>(),
+        &[],
+        &allowed.into_iter().collect::<Vec<_>>(),
+        costs,
+    )
+}
+
+// The proper backdoor graph as an ADMG together with the nodes that are allowed
+// in adjustment sets, or None if the effect is not amenable or x and y intersect.
+// The nodes range up to the largest one in the graph, x, y and extra.
+fn backdoor_instance(
+    graph: &EdgeLists,
+    x: &BTreeSet<usize>,
+    y: &BTreeSet<usize>,
+    extra: &BTreeSet<usize>,
+) -> Result<Option<(EdgeLists, BTreeSet<usize>)>, ParseGraphError> {
+    let admg = if graph.contains_key("<->") {
+        Graph::new(graph, &Ruletable::ancestors_admg())?;
+        let mut directed = graph.get("-->").cloned().unwrap_or_default();
+        directed.extend(graph.get("<--").into_iter().flatten().map(|&(u, v)| (v, u)));
+        HashMap::from([
+            ("-->".to_owned(), directed),
+            ("<->".to_owned(), graph["<->"].clone()),
+        ])
+    } else {
+        let not_amenable_table = Ruletable::not_amenable_cpdag();
+        let cpdag = Graph::new(graph, &not_amenable_table)?;
+        if !reach_set(&cpdag, &not_amenable_table, &[("X", x)]).is_disjoint(y) {
+            return Ok(None);
+        }
+        dag_in_class(graph)
+    };
+    if !x.is_disjoint(y) {
+        return Ok(None);
+    }
+
+    let ancestors_table = Ruletable::ancestors_admg();
+    let descendants_table = Ruletable::descendants_admg();
+    let graph = Graph::new(&admg, &ancestors_table)?;
+    let descendants = reach_set(&graph, &descendants_table, &[("X", x)]);
+    let causal_nodes: BTreeSet<_> = reach_set(
+        &Graph::new(&without_edges_from(&admg, x, |_| true), &ancestors_table)?,
+        &ancestors_table,
+        &[("X", y)],
+    )
+    .intersection(&descendants)
+    .filter(|v| !x.contains(v))
+    .copied()
+    .collect();
+    let mut excluded = reach_set(&graph, &descendants_table, &[("X", &causal_nodes)]);
+    excluded.extend(x);
+    excluded.extend(y);
+
+    let num_nodes = admg
+        .values()
+        .flatten()
+        .flat_map(|&(u, v)| [u, v])
+        .chain(x.iter().chain(y).chain(extra).copied())
+        .max()
+        .map_or(0, |v| v + 1);
+    let allowed = (0..num_nodes).filter(|v| !excluded.contains(v)).collect();
+    let backdoor_graph = without_edges_from(&admg, x, |v| causal_nodes.contains(v));
+    Ok(Some((backdoor_graph, allowed)))
+}
+
+/// Iterator over adjustment sets returned by [`enumerate_adjustment_sets`].
+pub struct AdjustmentSets(Inner);
+
+enum Inner {
+    Empty,
+    Minimal(Box<MinimalSeparators>, usize),
+    Valid(Box<ValidSearch>),
+}
+
+impl AdjustmentSets {
+    fn empty() -> Self {
+        AdjustmentSets(Inner::Empty)
+    }
+}
+
+impl Iterator for AdjustmentSets {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            Inner::Empty => None,
+            Inner::Minimal(separators, max_size) => separators.find(|z| z.len() <= *max_size),
+            Inner::Valid(search) => search.next(),
+        }
+    }
+}
+
+// Lists the d-separators z of x and y with included ⊆ z ⊆ candidates. The set z
+// of the rule table is updated in place for each separation test.
+struct ValidSearch {
+    graph: Graph,
+    sets: Sets,
+    z_id: usize,
+    z: BTreeSet<usize>,
+    dconnected_table: Ruletable,
+    ancestors_table: Ruletable,
+    x: BTreeSet<usize>,
+    y: BTreeSet<usize>,
+    max_size: usize,
+    stack: Vec<(BTreeSet<usize>, BTreeSet<usize>)>,
+}
+
+impl ValidSearch {
+    // a separator exists if and only if the candidates that are ancestors of x,
+    // y or the included nodes form one
+    fn feasible(&mut self, included: &BTreeSet<usize>, candidates: &BTreeSet<usize>) -> bool {
+        if included.len() > self.max_size {
+            return false;
+        }
+        let mut relevant = &self.x | &self.y;
+        relevant.extend(included);
+        let ancestors = reach_set(&self.graph, &self.ancestors_table, &[("X", &relevant)]);
+        let z: BTreeSet<_> = candidates
+            .iter()
+            .filter(|v| included.contains(v) || ancestors.contains(v))
+            .copied()
+            .collect();
+
+        for &v in self.z.difference(&z) {
+            self.sets.remove(self.z_id, v);
+        }
+        for &v in z.difference(&self.z) {
+            self.sets.insert(self.z_id, v);
+        }
+        self.z = z;
+        reach(
+            &self.graph,
+            &self.sets,
+            &self.dconnected_table,
+            &Settings::default(),
+        )
+        .iter()
+        .all(|v| !self.y.contains(v))
+    }
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        while let Some((included, candidates)) = self.stack.pop() {
+            let v = match candidates.difference(&included).next() {
+                Some(&v) => v,
+                None => return Some(included.into_iter().collect()),
+            };
+
+            let mut with_v = included.clone();
+            with_v.insert(v);
+            if self.feasible(&with_v, &candidates) {
+                self.stack.push((with_v, candidates.clone()));
+            }
+            let mut without_v = candidates;
+            without_v.remove(&v);
+            if self.feasible(&included, &without_v) {
+                self.stack.push((included, without_v));
+            }
+        }
+        None
+    }
+}
+
+// copy of the graph without the directed edges from x into the given nodes
+fn without_edges_from(
+    graph: &EdgeLists,
+    x: &BTreeSet<usize>,
+    into: impl Fn(&usize) -> bool,
+) -> EdgeLists {
+    let mut graph = graph.clone();
+    if let Some(edges) = graph.get_mut("-->") {
+        edges.retain(|(u, v)| !x.contains(u) || !into(v));
+    }
+    graph
+}
+
+// DAG in the equivalence class of a CPDAG
+fn dag_in_class(cpdag: &EdgeLists) -> EdgeLists {
+    let mut directed = cpdag.get("-->").cloned().unwrap_or_default();
+    directed.extend(cpdag.get("<--").into_iter().flatten().map(|&(u, v)| (v, u)));
+    directed.extend(orient_chordal(
+        cpdag.get("---").into_iter().flatten().copied(),
+    ));
+    HashMap::from([("-->".to_owned(), directed)])
+}
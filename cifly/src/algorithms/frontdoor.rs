@@ -0,0 +1,49 @@
+//! Finding front-door adjustment sets in DAGs.
+
+use super::{reach_set, to_set, EdgeLists};
+use crate::{instance::Graph, ruletable::Ruletable, ParseGraphError};
+
+/// Finds a front-door adjustment set `z` relative to `x` and `y` in a DAG with
+/// `i ⊆ z ⊆ r`.
+///
+/// Implements the algorithm of Wienöbst et al. (2024), which returns the largest
+/// such front-door adjustment set in ascending order, or `None` if there is none.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->`.
+pub fn frontdoor(
+    dag: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    i: &[usize],
+    r: &[usize],
+) -> Result<Option<Vec<usize>>, ParseGraphError> {
+    let (x, y, i) = (to_set(x), to_set(y), to_set(i));
+    let backdoor_connected_table = Ruletable::backdoor_connected_dag();
+    let graph = Graph::new(dag, &backdoor_connected_table)?;
+
+    let backdoor_connected = reach_set(&graph, &backdoor_connected_table, &[("X", &x)]);
+    let zi = r
+        .iter()
+        .copied()
+        .filter(|v| !backdoor_connected.contains(v))
+        .collect();
+    let ancestors = reach_set(&graph, &Ruletable::ancestors_admg(), &[("X", &y)]);
+    let forbidden = reach_set(
+        &graph,
+        &Ruletable::frontdoor_forbidden_dag(),
+        &[("Y", &y), ("A", &ancestors), ("Z", &zi), ("X", &x)],
+    );
+    let zii = zi.difference(&forbidden).copied().collect();
+
+    let intercepted = reach_set(
+        &graph,
+        &Ruletable::intercepted_paths_dag(),
+        &[("X", &x), ("Z", &zii)],
+    );
+    if i.is_subset(&zii) && y.is_disjoint(&intercepted) {
+        Ok(Some(zii.into_iter().collect()))
+    } else {
+        Ok(None)
+    }
+}
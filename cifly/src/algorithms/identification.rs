@@ -0,0 +1,259 @@
+//! Identification of causal effects in ADMGs with the ID algorithm.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::{reach_set, to_set, EdgeLists};
+use crate::{estimand::Estimand, instance::Graph, ruletable::Ruletable, ParseGraphError};
+
+/// Hedge witnessing that a causal effect is not identifiable.
+///
+/// The nodes `f` and `f_prime` form two c-forests with the same roots, which are
+/// ancestors of the outcomes. The nodes in `f_prime` are a proper subset of those
+/// in `f` and contain no treatment, whereas `f` does (Shpitser and Pearl, 2006).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hedge {
+    pub f: Vec<usize>,
+    pub f_prime: Vec<usize>,
+}
+
+/// Result of the ID algorithm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identification {
+    /// The effect equals the estimand.
+    Identified(Estimand),
+    /// The effect is not identifiable as witnessed by the hedge.
+    NotIdentifiable(Hedge),
+}
+
+/// Identifies the causal effect of `x` on `y` in an ADMG with the ID algorithm.
+///
+/// Implements the algorithm of Shpitser and Pearl (2006), which builds on the
+/// c-component factorization of Tian and Pearl (2002). Returns an estimand in
+/// terms of the observational distribution equal to the interventional distribution
+/// of `y` under interventions on `x` if the effect is identifiable, and a hedge
+/// otherwise. The algorithm is complete, so a hedge is returned exactly if the
+/// effect is not identifiable. Nodes in both `x` and `y` are treated as treatments.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->`, `<--` and `<->`.
+pub fn identify(
+    admg: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+) -> Result<Identification, ParseGraphError> {
+    let district_table = Ruletable::district_admg();
+    let graph = Graph::new(admg, &district_table)?;
+    let x = to_set(x);
+    let y: BTreeSet<_> = y.iter().filter(|v| !x.contains(v)).copied().collect();
+
+    let mut directed: Vec<_> = admg.get("-->").into_iter().flatten().copied().collect();
+    directed.extend(admg.get("<--").into_iter().flatten().map(|&(u, v)| (v, u)));
+    let mut nodes: BTreeSet<_> = &x | &y;
+    nodes.extend(admg.values().flatten().flat_map(|&(u, v)| [u, v]));
+
+    let id = Id {
+        graph,
+        district_table,
+        ancestors_table: Ruletable::ancestors_subgraph_admg(),
+        order: topological_order(&nodes, &directed),
+    };
+    let distribution = Distribution {
+        estimand: Estimand::probability(&nodes, &BTreeSet::new()),
+        nodes: nodes.clone(),
+    };
+    Ok(match id.identify(&y, &x, &distribution, &nodes) {
+        Ok(estimand) => Identification::Identified(estimand),
+        Err(hedge) => Identification::NotIdentifiable(hedge),
+    })
+}
+
+// the distribution at the current step of the algorithm as a joint estimand over
+// nodes, nodes outside of it that occur in the estimand are fixed
+struct Distribution {
+    estimand: Estimand,
+    nodes: BTreeSet<usize>,
+}
+
+impl Distribution {
+    fn marginal(&self, nodes: &BTreeSet<usize>) -> Estimand {
+        Estimand::sum(&(&self.nodes - nodes), self.estimand.clone())
+    }
+
+    fn conditional(&self, v: usize, given: &BTreeSet<usize>) -> Estimand {
+        match &self.estimand {
+            Estimand::Probability { given: fixed, .. } => {
+                let mut given = given.clone();
+                given.extend(fixed.iter().copied());
+                Estimand::probability(&BTreeSet::from([v]), &given)
+            }
+            _ if given.is_empty() => self.marginal(&BTreeSet::from([v])),
+            _ => {
+                let mut joint = given.clone();
+                joint.insert(v);
+                Estimand::Quotient(
+                    Box::new(self.marginal(&joint)),
+                    Box::new(self.marginal(given)),
+                )
+            }
+        }
+    }
+}
+
+struct Id {
+    graph: Graph,
+    district_table: Ruletable,
+    ancestors_table: Ruletable,
+    order: Vec<usize>,
+}
+
+impl Id {
+    // ancestors of y in the subgraph induced by nodes without the edges into w
+    fn ancestors(
+        &self,
+        y: &BTreeSet<usize>,
+        nodes: &BTreeSet<usize>,
+        w: &BTreeSet<usize>,
+    ) -> BTreeSet<usize> {
+        reach_set(
+            &self.graph,
+            &self.ancestors_table,
+            &[("X", y), ("V", nodes), ("W", w)],
+        )
+    }
+
+    // c-components of the subgraph induced by nodes
+    fn districts(&self, nodes: &BTreeSet<usize>) -> Vec<BTreeSet<usize>> {
+        let mut remaining = nodes.clone();
+        let mut districts = Vec::new();
+        while let Some(&v) = remaining.first() {
+            let district = reach_set(
+                &self.graph,
+                &self.district_table,
+                &[("X", &BTreeSet::from([v])), ("V", nodes)],
+            );
+            remaining.retain(|u| !district.contains(u));
+            districts.push(district);
+        }
+        districts
+    }
+
+    // P(v | predecessors of v in nodes) for every v in s
+    fn factors(
+        &self,
+        s: &BTreeSet<usize>,
+        distribution: &Distribution,
+        nodes: &BTreeSet<usize>,
+    ) -> Vec<Estimand> {
+        let mut predecessors = BTreeSet::new();
+        let mut factors = Vec::new();
+        for &v in self.order.iter().filter(|v| nodes.contains(v)) {
+            if s.contains(&v) {
+                factors.push(distribution.conditional(v, &predecessors));
+            }
+            predecessors.insert(v);
+        }
+        factors
+    }
+
+    fn identify(
+        &self,
+        y: &BTreeSet<usize>,
+        x: &BTreeSet<usize>,
+        distribution: &Distribution,
+        nodes: &BTreeSet<usize>,
+    ) -> Result<Estimand, Hedge> {
+        // line 1
+        if x.is_empty() {
+            return Ok(distribution.marginal(y));
+        }
+
+        // line 2
+        let ancestors = self.ancestors(y, nodes, &BTreeSet::new());
+        if ancestors != *nodes {
+            let distribution = Distribution {
+                estimand: distribution.marginal(&ancestors),
+                nodes: ancestors.clone(),
+            };
+            return self.identify(y, &(x & &ancestors), &distribution, &ancestors);
+        }
+
+        // line 3
+        let ancestors = self.ancestors(y, nodes, x);
+        let w: BTreeSet<_> = nodes
+            .iter()
+            .filter(|v| !x.contains(v) && !ancestors.contains(v))
+            .copied()
+            .collect();
+        if !w.is_empty() {
+            return self.identify(y, &(x | &w), distribution, nodes);
+        }
+
+        // line 4
+        let remaining = nodes - x;
+        let mut districts = self.districts(&remaining);
+        if districts.len() > 1 {
+            let factors = districts
+                .iter()
+                .map(|s| self.identify(s, &(nodes - s), distribution, nodes))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(Estimand::sum(&(&remaining - y), Estimand::product(factors)));
+        }
+        let s = districts.pop().unwrap_or_default();
+
+        // line 5
+        let full_districts = self.districts(nodes);
+        if full_districts.len() == 1 {
+            return Err(Hedge {
+                f: nodes.iter().copied().collect(),
+                f_prime: s.into_iter().collect(),
+            });
+        }
+
+        // line 6
+        if full_districts.contains(&s) {
+            let factors = self.factors(&s, distribution, nodes);
+            return Ok(Estimand::sum(&(&s - y), Estimand::product(factors)));
+        }
+
+        // line 7
+        let s_prime = full_districts
+            .into_iter()
+            .find(|d| s.is_subset(d))
+            .expect("district of subgraph should be contained in a district of the graph");
+        let distribution = Distribution {
+            estimand: Estimand::product(self.factors(&s_prime, distribution, nodes)),
+            nodes: s_prime.clone(),
+        };
+        self.identify(y, &(x & &s_prime), &distribution, &s_prime)
+    }
+}
+
+// Kahn's algorithm preferring small nodes, nodes on cycles are appended in the end
+fn topological_order(nodes: &BTreeSet<usize>, directed: &[(usize, usize)]) -> Vec<usize> {
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut in_degree: HashMap<usize, usize> = nodes.iter().map(|&v| (v, 0)).collect();
+    for &(u, v) in directed.iter() {
+        children.entry(u).or_default().push(v);
+        *in_degree.entry(v).or_default() += 1;
+    }
+
+    let mut ready: BTreeSet<_> = nodes
+        .iter()
+        .filter(|v| in_degree[v] == 0)
+        .copied()
+        .collect();
+    let mut order = Vec::new();
+    while let Some(v) = ready.pop_first() {
+        order.push(v);
+        for u in children.get(&v).into_iter().flatten() {
+            let degree = in_degree.get_mut(u).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.insert(*u);
+            }
+        }
+    }
+    let ordered = to_set(&order);
+    order.extend(nodes.iter().filter(|v| !ordered.contains(v)));
+    order
+}
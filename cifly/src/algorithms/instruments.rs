@@ -0,0 +1,112 @@
+//! Finding conditional instrumental variables in ADMGs.
+
+use std::collections::BTreeSet;
+
+use super::{reach_set, separators::nearest_dsep, to_set, EdgeLists};
+use crate::{instance::Graph, ruletable::Ruletable, ParseGraphError};
+
+/// Conditional instrument `(z, w)` consisting of instruments `z` and conditioning set `w`.
+pub type Instrument = (Vec<usize>, Vec<usize>);
+
+/// Finds the optimal conditional instrument `(z, w)` for the effect of `x` on `y`
+/// in an ADMG.
+///
+/// Implements the criterion of Henckel et al. (2024). The returned instrument is
+/// only guaranteed to be optimal if there is no adjustment set relative to `x`
+/// and `y`, which is not checked here. Returns `None` if `y` is not a descendant
+/// of `x` or if the criterion does not yield a valid instrument.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->` and `<->`.
+pub fn optimal_instrument(
+    admg: &EdgeLists,
+    x: usize,
+    y: usize,
+) -> Result<Option<Instrument>, ParseGraphError> {
+    let descendants_table = Ruletable::descendants_admg();
+    let optimal_iv_table = Ruletable::optimal_iv_admg();
+    let graph = Graph::new(admg, &descendants_table)?;
+    let (xs, ys) = (to_set(&[x]), to_set(&[y]));
+
+    let descendants = reach_set(&graph, &descendants_table, &[("X", &xs)]);
+    if !descendants.contains(&y) {
+        return Ok(None);
+    }
+
+    let w = reach_set(
+        &graph,
+        &optimal_iv_table,
+        &[("S", &ys), ("D", &descendants), ("F", &xs)],
+    );
+    let z: BTreeSet<_> = reach_set(
+        &graph,
+        &optimal_iv_table,
+        &[("S", &xs), ("D", &descendants)],
+    )
+    .difference(&w)
+    .copied()
+    .collect();
+
+    let is_parent = |(u, v): &(usize, usize)| z.contains(u) && *v == x;
+    let is_sibling =
+        |(u, v): &(usize, usize)| (z.contains(u) && *v == x) || (*u == x && z.contains(v));
+    let contains_parent = admg.get("-->").into_iter().flatten().any(is_parent);
+    let contains_sibling = admg.get("<->").into_iter().flatten().any(is_sibling);
+    if !z.is_empty() && (contains_parent || contains_sibling) {
+        Ok(Some((z.into_iter().collect(), w.into_iter().collect())))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Finds a conditional instrument `(z, w)` with a single instrument `z` for the
+/// effect of `x` on `y` in an ADMG.
+///
+/// Implements the sound and complete algorithm of van der Zander et al. (2015):
+/// if such an instrument exists, one is returned, otherwise `None`. Candidate
+/// instruments are tried in ascending order.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->` and `<->`.
+pub fn sound_and_complete_instrument(
+    admg: &EdgeLists,
+    x: usize,
+    y: usize,
+) -> Result<Option<Instrument>, ParseGraphError> {
+    let ancestors_table = Ruletable::ancestors_admg();
+    let descendants_table = Ruletable::descendants_admg();
+    let graph = Graph::new(admg, &ancestors_table)?;
+    let (xs, ys) = (to_set(&[x]), to_set(&[y]));
+
+    let ancestors = reach_set(&graph, &ancestors_table, &[("X", &ys)]);
+    let descendants = reach_set(&graph, &descendants_table, &[("X", &xs)]);
+    let causal_nodes: BTreeSet<_> = ancestors
+        .intersection(&descendants)
+        .copied()
+        .filter(|&v| v != x)
+        .collect();
+    let mut forbidden = reach_set(&graph, &descendants_table, &[("X", &causal_nodes)]);
+    forbidden.insert(x);
+    let num_nodes = graph.num_vertices().max(x + 1).max(y + 1);
+    let non_forbidden: BTreeSet<_> = (0..num_nodes).filter(|v| !forbidden.contains(v)).collect();
+
+    // remove the first edges of causal paths from x to y
+    let mut modified = admg.clone();
+    if let Some(edges) = modified.get_mut("-->") {
+        edges.retain(|(u, v)| *u != x || !causal_nodes.contains(v));
+    }
+    let modified_graph = Graph::new(&modified, &ancestors_table)?;
+
+    let dconnected_table = Ruletable::dconnected_admg();
+    for &z in non_forbidden.iter().filter(|&&z| z != y) {
+        let zs = to_set(&[z]);
+        let w = match nearest_dsep(&modified_graph, &ys, &zs, &BTreeSet::new(), &non_forbidden) {
+            Some(w) => w,
+            None => continue,
+        };
+        if reach_set(&graph, &dconnected_table, &[("X", &xs), ("Z", &w)]).contains(&z) {
+            return Ok(Some((vec![z], w.into_iter().collect())));
+        }
+    }
+    Ok(None)
+}
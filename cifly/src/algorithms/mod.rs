@@ -0,0 +1,112 @@
+//! Causal inference algorithms built on [`reach`] and the bundled rule tables.
+//!
+//! These are the algorithms from the examples on the [CIfly website](https://cifly.dev),
+//! implemented once in Rust so that they can be shared by the wrapper packages.
+//! Graphs are passed as maps from edge types to edge lists, as for [`Graph::new`].
+//! DAGs use the edge type `-->`, CPDAGs additionally use `---` and ADMGs `<->`.
+//! MAGs and PAGs use `-->` and `<->` as well as the circle marks `o->` and `o-o`.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    instance::{is_valid_weight, Graph, Sets},
+    reach::{reach, Settings},
+    ruletable::Ruletable,
+    ParseGraphError,
+};
+
+pub mod adjustment;
+pub mod frontdoor;
+pub mod identification;
+pub mod instruments;
+pub mod pag;
+pub mod separators;
+
+pub use adjustment::{
+    enumerate_adjustment_sets, is_cpdag_adjustment, min_cost_adjustment, optimal_adjustment,
+    parent_aid, AdjustmentOptions, AdjustmentSets,
+};
+pub use frontdoor::frontdoor;
+pub use identification::{identify, Hedge, Identification};
+pub use instruments::{optimal_instrument, sound_and_complete_instrument, Instrument};
+pub use pag::{
+    is_pag_adjustment, is_pag_amenable, is_pag_m_connected, pag_adjustment,
+    pag_possible_descendants,
+};
+pub use separators::{
+    enumerate_minimal_separators, find_min_cost_dsep, find_minimal_dsep, find_nearest_dsep,
+    MinimalSeparators,
+};
+
+/// Map from edge types to edge lists describing a graph, as passed to [`Graph::new`].
+pub type EdgeLists = HashMap<String, Vec<(usize, usize)>>;
+
+// runs reach for the sets given as (name, elements) pairs, duplicates are ignored
+fn reach_set(
+    graph: &Graph,
+    ruletable: &Ruletable,
+    sets: &[(&str, &BTreeSet<usize>)],
+) -> BTreeSet<usize> {
+    let sets: HashMap<_, _> = sets
+        .iter()
+        .map(|&(name, set)| (name.to_owned(), set.iter().copied().collect()))
+        .collect();
+    let sets = Sets::new(&sets, ruletable).expect("sets should be declared in rule table");
+    reach(graph, &sets, ruletable, &Settings::default())
+        .into_iter()
+        .collect()
+}
+
+fn to_set(nodes: &[usize]) -> BTreeSet<usize> {
+    nodes.iter().copied().collect()
+}
+
+// costs follow the rules for node weights, infinite costs are allowed
+fn check_costs(costs: &[f64]) -> Result<(), ParseGraphError> {
+    match costs
+        .iter()
+        .enumerate()
+        .find(|&(_, &c)| !is_valid_weight(c))
+    {
+        Some((v, c)) => Err(ParseGraphError(format!("node {v} has invalid cost {c}"))),
+        None => Ok(()),
+    }
+}
+
+// Orients the edges of a chordal undirected graph along a maximum cardinality
+// search ordering, which creates no cycles and no unshielded colliders.
+fn orient_chordal(edges: impl Iterator<Item = (usize, usize)>) -> Vec<(usize, usize)> {
+    let edges: Vec<_> = edges.collect();
+    let mut neighbors: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &(u, v) in edges.iter() {
+        neighbors.entry(u).or_default().push(v);
+        neighbors.entry(v).or_default().push(u);
+    }
+
+    let mut weight: HashMap<usize, usize> = neighbors.keys().map(|&v| (v, 0)).collect();
+    let mut position = HashMap::new();
+    while let Some(v) = weight
+        .iter()
+        .max_by_key(|&(&v, &w)| (w, std::cmp::Reverse(v)))
+        .map(|(&v, _)| v)
+    {
+        weight.remove(&v);
+        position.insert(v, position.len());
+        for u in neighbors[&v].iter() {
+            if let Some(w) = weight.get_mut(u) {
+                *w += 1;
+            }
+        }
+    }
+
+    edges
+        .into_iter()
+        .map(|(u, v)| {
+            if position[&u] < position[&v] {
+                (u, v)
+            } else {
+                (v, u)
+            }
+        })
+        .collect()
+}
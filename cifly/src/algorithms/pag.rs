@@ -0,0 +1,324 @@
+//! Generalized adjustment criterion in MAGs and PAGs.
+//!
+//! Graphs use the edge types `-->`, `<->`, `o->` and `o-o`, as well as `<--` and
+//! `<-o` for the reversed edges. MAGs are handled as PAGs without circle marks.
+//! Selection bias, that is, undirected edges, is not supported.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::{orient_chordal, reach_set, to_set, EdgeLists};
+use crate::{instance::Graph, ruletable::Ruletable, ParseGraphError};
+
+/// Checks whether the effect of `x` on `y` is amenable to adjustment in a PAG.
+///
+/// The effect is amenable if every proper possibly directed path from `x` to `y`
+/// starts with a visible directed edge out of `x` (Perković et al., 2018).
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->`, `<--`,
+/// `<->`, `o->`, `<-o` and `o-o`.
+pub fn is_pag_amenable(pag: &EdgeLists, x: &[usize], y: &[usize]) -> Result<bool, ParseGraphError> {
+    let pag = Pag::new(pag)?;
+    Ok(pag.is_amenable(&to_set(x), &to_set(y)))
+}
+
+/// Checks whether `z` is a valid adjustment set relative to `x` and `y` in a PAG.
+///
+/// Follows the generalized adjustment criterion of Perković et al. (2018): the
+/// effect has to be amenable, `z` may not contain possible descendants of nodes on
+/// proper possibly causal paths and all proper definite status non-causal paths
+/// from `x` to `y` have to be blocked by `z`. The last condition is checked in a
+/// MAG in the equivalence class, as shown by van der Zander et al. (2019).
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->`, `<--`,
+/// `<->`, `o->`, `<-o` and `o-o`.
+pub fn is_pag_adjustment(
+    pag: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    z: &[usize],
+) -> Result<bool, ParseGraphError> {
+    let pag = Pag::new(pag)?;
+    let (x, y, z) = (to_set(x), to_set(y), to_set(z));
+    if !pag.is_amenable(&x, &y) {
+        return Ok(false);
+    }
+    let forbidden = pag.forbidden(&x, &y);
+    Ok(forbidden.is_disjoint(&z) && pag.blocks_backdoor_paths(&x, &y, &z, &forbidden)?)
+}
+
+/// Constructs an adjustment set relative to `x` and `y` in a PAG.
+///
+/// Returns the set of possible ancestors of `x` and `y` that are neither in `x`
+/// or `y` nor forbidden, in ascending order, which is a valid adjustment set if
+/// and only if one exists (Perković et al., 2018). Returns `None` otherwise.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->`, `<--`,
+/// `<->`, `o->`, `<-o` and `o-o`.
+pub fn pag_adjustment(
+    pag: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+) -> Result<Option<Vec<usize>>, ParseGraphError> {
+    let pag = Pag::new(pag)?;
+    let (x, y) = (to_set(x), to_set(y));
+    if !pag.is_amenable(&x, &y) {
+        return Ok(None);
+    }
+    let forbidden = pag.forbidden(&x, &y);
+    let z: BTreeSet<_> = pag
+        .possible_ancestors(&(&x | &y), &BTreeSet::new())
+        .into_iter()
+        .filter(|v| !x.contains(v) && !y.contains(v) && !forbidden.contains(v))
+        .collect();
+    if pag.blocks_backdoor_paths(&x, &y, &z, &forbidden)? {
+        Ok(Some(z.into_iter().collect()))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the possible descendants of `x` in a PAG, including `x`, in ascending order.
+///
+/// A node is a possible descendant of `x` if it is reached on a possibly directed
+/// path from `x`, that is, a path without an arrowhead or tail pointing back
+/// towards `x`.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->`, `<--`,
+/// `<->`, `o->`, `<-o` and `o-o`.
+pub fn pag_possible_descendants(
+    pag: &EdgeLists,
+    x: &[usize],
+) -> Result<Vec<usize>, ParseGraphError> {
+    let pag = Pag::new(pag)?;
+    Ok(pag.possible_descendants(&to_set(x)).into_iter().collect())
+}
+
+/// Checks whether `x` and `y` are m-connected given `z` by a definite status walk in a PAG.
+///
+/// A node `v` in a triple `u *-* v *-* w` of the walk is a definite collider if
+/// both edges have an arrowhead at `v`, and a definite non-collider if one of the
+/// edges has a tail at `v` or if both have a circle at `v` and `u` and `w` are not
+/// adjacent. The walk may not turn back on the edge it came from, and every other
+/// triple has to be of definite status with colliders being ancestors of `z` and
+/// non-colliders not in `z`. Unlike the rule table `dconnected_marks_pag`, this
+/// detects the non-colliders of unshielded triples `u *-o v o-* w`. Returns `true`
+/// if `x` and `y` intersect.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->`, `<--`,
+/// `<->`, `o->`, `<-o` and `o-o`.
+pub fn is_pag_m_connected(
+    pag: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    z: &[usize],
+) -> Result<bool, ParseGraphError> {
+    let pag = Pag::new(pag)?;
+    Ok(pag.is_m_connected(&to_set(x), &to_set(y), &to_set(z)))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Tail,
+    Arrow,
+    Circle,
+}
+
+struct Pag {
+    graph: Graph,
+    // marks of the edges at the node and at the neighbor
+    adjacent: BTreeMap<usize, BTreeMap<usize, (Mark, Mark)>>,
+}
+
+impl Pag {
+    fn new(edge_lists: &EdgeLists) -> Result<Pag, ParseGraphError> {
+        let graph = Graph::new(edge_lists, &Ruletable::possible_descendants_pag())?;
+        let mut adjacent: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+        for (edge_type, edges) in edge_lists.iter() {
+            let marks = match edge_type.as_str() {
+                "-->" => (Mark::Tail, Mark::Arrow),
+                "<--" => (Mark::Arrow, Mark::Tail),
+                "<->" => (Mark::Arrow, Mark::Arrow),
+                "o->" => (Mark::Circle, Mark::Arrow),
+                "<-o" => (Mark::Arrow, Mark::Circle),
+                _ => (Mark::Circle, Mark::Circle),
+            };
+            for &(u, v) in edges.iter() {
+                adjacent.entry(u).or_default().insert(v, marks);
+                adjacent.entry(v).or_default().insert(u, (marks.1, marks.0));
+            }
+        }
+        Ok(Pag { graph, adjacent })
+    }
+
+    fn neighbors(&self, v: usize) -> impl Iterator<Item = (usize, Mark, Mark)> + '_ {
+        self.adjacent
+            .get(&v)
+            .into_iter()
+            .flatten()
+            .map(|(&u, &(mark_v, mark_u))| (u, mark_v, mark_u))
+    }
+
+    fn is_adjacent(&self, u: usize, v: usize) -> bool {
+        self.adjacent.get(&u).is_some_and(|n| n.contains_key(&v))
+    }
+
+    fn possible_ancestors(&self, x: &BTreeSet<usize>, w: &BTreeSet<usize>) -> BTreeSet<usize> {
+        reach_set(
+            &self.graph,
+            &Ruletable::possible_ancestors_pag(),
+            &[("X", x), ("W", w)],
+        )
+    }
+
+    fn possible_descendants(&self, x: &BTreeSet<usize>) -> BTreeSet<usize> {
+        reach_set(
+            &self.graph,
+            &Ruletable::possible_descendants_pag(),
+            &[("X", x)],
+        )
+    }
+
+    // nodes other than x on proper possibly causal paths from x to y
+    fn causal_nodes(&self, x: &BTreeSet<usize>, y: &BTreeSet<usize>) -> BTreeSet<usize> {
+        let ancestors = self.possible_ancestors(y, x);
+        self.possible_descendants(x)
+            .intersection(&ancestors)
+            .filter(|v| !x.contains(v))
+            .copied()
+            .collect()
+    }
+
+    fn forbidden(&self, x: &BTreeSet<usize>, y: &BTreeSet<usize>) -> BTreeSet<usize> {
+        self.possible_descendants(&self.causal_nodes(x, y))
+    }
+
+    fn is_amenable(&self, x: &BTreeSet<usize>, y: &BTreeSet<usize>) -> bool {
+        let ancestors = self.possible_ancestors(y, x);
+        x.iter().all(|&u| {
+            self.neighbors(u)
+                .filter(|&(v, mark_u, _)| {
+                    mark_u != Mark::Arrow && !x.contains(&v) && ancestors.contains(&v)
+                })
+                .all(|(v, mark_u, mark_v)| {
+                    mark_u == Mark::Tail && mark_v == Mark::Arrow && self.is_visible(u, v)
+                })
+        })
+    }
+
+    // The edge u --> d is visible if some node c not adjacent to d has an edge
+    // c *-> u or a collider path c *-> v <-> ... <-> u through parents of d.
+    fn is_visible(&self, u: usize, d: usize) -> bool {
+        let mut visited = BTreeSet::from([u]);
+        let mut stack = vec![u];
+        while let Some(v) = stack.pop() {
+            for (c, mark_v, mark_c) in self.neighbors(v) {
+                if mark_v != Mark::Arrow {
+                    continue;
+                }
+                if c != d && !self.is_adjacent(c, d) {
+                    return true;
+                }
+                let is_parent_of_d = self
+                    .adjacent
+                    .get(&d)
+                    .and_then(|n| n.get(&c))
+                    .is_some_and(|&marks| marks == (Mark::Arrow, Mark::Tail));
+                if mark_c == Mark::Arrow && is_parent_of_d && visited.insert(c) {
+                    stack.push(c);
+                }
+            }
+        }
+        false
+    }
+
+    // nodes with a directed path u --> ... --> v into some v in z, including z
+    fn definite_ancestors(&self, z: &BTreeSet<usize>) -> BTreeSet<usize> {
+        let mut ancestors = z.clone();
+        let mut stack: Vec<_> = z.iter().copied().collect();
+        while let Some(v) = stack.pop() {
+            for (u, mark_v, mark_u) in self.neighbors(v) {
+                if mark_v == Mark::Arrow && mark_u == Mark::Tail && ancestors.insert(u) {
+                    stack.push(u);
+                }
+            }
+        }
+        ancestors
+    }
+
+    // searches over the edges (u, v) of definite status walks from x, which are
+    // extended by w whenever u *-* v *-* w is an open triple of definite status
+    fn is_m_connected(
+        &self,
+        x: &BTreeSet<usize>,
+        y: &BTreeSet<usize>,
+        z: &BTreeSet<usize>,
+    ) -> bool {
+        if !x.is_disjoint(y) {
+            return true;
+        }
+        let ancestors = self.definite_ancestors(z);
+        let mut stack: Vec<_> = x
+            .iter()
+            .flat_map(|&u| self.neighbors(u).map(move |(v, _, _)| (u, v)))
+            .collect();
+        let mut visited: BTreeSet<_> = stack.iter().copied().collect();
+        while let Some((u, v)) = stack.pop() {
+            if y.contains(&v) {
+                return true;
+            }
+            let mark_in = self.adjacent[&v][&u].0;
+            for (w, mark_out, _) in self.neighbors(v) {
+                if w == u {
+                    continue;
+                }
+                let is_open = match (mark_in, mark_out) {
+                    (Mark::Arrow, Mark::Arrow) => ancestors.contains(&v),
+                    (Mark::Tail, _) | (_, Mark::Tail) => !z.contains(&v),
+                    (Mark::Circle, Mark::Circle) => !self.is_adjacent(u, w) && !z.contains(&v),
+                    _ => false,
+                };
+                if is_open && visited.insert((v, w)) {
+                    stack.push((v, w));
+                }
+            }
+        }
+        false
+    }
+
+    // Checks m-separation in the proper backdoor graph of a MAG in the equivalence
+    // class. All edges from x into forbidden nodes are removed, which is safe as
+    // non-causal paths starting with them have a forbidden collider.
+    fn blocks_backdoor_paths(
+        &self,
+        x: &BTreeSet<usize>,
+        y: &BTreeSet<usize>,
+        z: &BTreeSet<usize>,
+        forbidden: &BTreeSet<usize>,
+    ) -> Result<bool, ParseGraphError> {
+        let mut directed = Vec::new();
+        let mut bidirected = Vec::new();
+        let mut circles = Vec::new();
+        for (&u, neighbors) in self.adjacent.iter() {
+            for (&v, &marks) in neighbors.range(u + 1..) {
+                match marks {
+                    (Mark::Arrow, Mark::Arrow) => bidirected.push((u, v)),
+                    (Mark::Circle, Mark::Circle) => circles.push((u, v)),
+                    (_, Mark::Arrow) => directed.push((u, v)),
+                    _ => directed.push((v, u)),
+                }
+            }
+        }
+        directed.extend(orient_chordal(circles.into_iter()));
+        directed.retain(|(u, v)| !x.contains(u) || !forbidden.contains(v));
+
+        let mag = HashMap::from([("-->".to_owned(), directed), ("<->".to_owned(), bidirected)]);
+        let dconnected_table = Ruletable::dconnected_admg();
+        let graph = Graph::new(&mag, &dconnected_table)?;
+        Ok(reach_set(&graph, &dconnected_table, &[("X", x), ("Z", z)]).is_disjoint(y))
+    }
+}
@@ -0,0 +1,368 @@
+//! Finding d-separators in DAGs and ADMGs.
+//!
+//! DAGs are handled as ADMGs without bidirected edges.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use super::{check_costs, reach_set, to_set, EdgeLists};
+use crate::{instance::Graph, ruletable::Ruletable, ParseGraphError};
+
+/// Finds a d-separator `z` of `x` and `y` with `i ⊆ z ⊆ r` that is nearest to `x`.
+///
+/// Implements the algorithm of van der Zander (2020) for ADMGs. Returns the
+/// separator in ascending order, or `None` if no such separator exists.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->` and `<->`.
+pub fn find_nearest_dsep(
+    admg: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    i: &[usize],
+    r: &[usize],
+) -> Result<Option<Vec<usize>>, ParseGraphError> {
+    let ancestors_table = Ruletable::ancestors_admg();
+    let graph = Graph::new(admg, &ancestors_table)?;
+    Ok(
+        nearest_dsep(&graph, &to_set(x), &to_set(y), &to_set(i), &to_set(r))
+            .map(|z| z.into_iter().collect()),
+    )
+}
+
+/// Finds a minimal d-separator `z` of `x` and `y` with `i ⊆ z ⊆ r`.
+///
+/// The separator is minimal in the sense that no proper subset containing `i` is
+/// a d-separator. Implements the algorithm of van der Zander (2020) for ADMGs,
+/// which combines the separators nearest to `x` and to `y`. Returns the separator
+/// in ascending order, or `None` if no such separator exists.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->` and `<->`.
+pub fn find_minimal_dsep(
+    admg: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    i: &[usize],
+    r: &[usize],
+) -> Result<Option<Vec<usize>>, ParseGraphError> {
+    let ancestors_table = Ruletable::ancestors_admg();
+    let graph = Graph::new(admg, &ancestors_table)?;
+    let (x, y, i) = (to_set(x), to_set(y), to_set(i));
+
+    let zx = match nearest_dsep(&graph, &x, &y, &i, &to_set(r)) {
+        Some(zx) => zx,
+        None => return Ok(None),
+    };
+    let zy = match nearest_dsep(&graph, &y, &x, &i, &zx) {
+        Some(zy) => zy,
+        None => return Ok(None),
+    };
+    let mut z: BTreeSet<_> = zx.intersection(&zy).copied().collect();
+    z.extend(&i);
+    Ok(Some(z.into_iter().collect()))
+}
+
+/// Enumerates all minimal d-separators `z` of `x` and `y` with `i ⊆ z ⊆ r`.
+///
+/// A separator is minimal if no proper subset containing `i` is a d-separator,
+/// as for [`find_minimal_dsep`]. The separators are listed lazily with polynomial
+/// delay, each in ascending order, and every separator is listed exactly once.
+/// The sets `x`, `y` and `i` are expected to be disjoint.
+///
+/// Following van der Zander et al. (2019), the minimal d-separators correspond to
+/// the minimal vertex separators in the moral graph of the ancestors of `x`, `y`
+/// and `i`. These are listed by a backtracking search that decides for one node
+/// after the other whether it lies on the side of `x` or in the separator, only
+/// descending into branches that still contain a separator.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->` and `<->`.
+pub fn enumerate_minimal_separators(
+    admg: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    i: &[usize],
+    r: &[usize],
+) -> Result<MinimalSeparators, ParseGraphError> {
+    let ancestors_table = Ruletable::ancestors_admg();
+    let graph = Graph::new(admg, &ancestors_table)?;
+    let (x, y, i) = (to_set(x), to_set(y), to_set(i));
+
+    let mut relevant = &x | &y;
+    relevant.extend(&i);
+    let ancestors = reach_set(&graph, &ancestors_table, &[("X", &relevant)]);
+    let r = r
+        .iter()
+        .copied()
+        .filter(|v| ancestors.contains(v) && !relevant.contains(v))
+        .collect();
+
+    let mut separators = MinimalSeparators {
+        graph,
+        closure_table: Ruletable::closure_admg(),
+        y,
+        i,
+        r,
+        ancestors,
+        stack: Vec::new(),
+    };
+    if let Some(root) = separators.branch(x, BTreeSet::new()) {
+        separators.stack.push(root);
+    }
+    Ok(separators)
+}
+
+/// Finds a d-separator `z` of `x` and `y` with `i ⊆ z ⊆ r` of minimum total cost.
+///
+/// Node `v` has cost `costs[v]`, or one if there is no such entry, so passing no
+/// costs yields a separator of minimum size. Nodes with infinite cost are never
+/// in the separator. Returns the separator in ascending order together with the
+/// sum of the costs of its nodes, including those in `i`, or `None` if no such
+/// separator exists.
+///
+/// Following van der Zander et al. (2019), a minimum-cost separator corresponds
+/// to a minimum vertex cut between `x` and `y` in the moral graph of the ancestors
+/// of `x`, `y` and `i` without the nodes in `i`. The moral graph is obtained with
+/// the closure rule table and the cut from a maximum flow with the Edmonds-Karp
+/// algorithm, taking the cut closest to `x` among all minimum cuts.
+///
+/// # Errors
+/// Returns an error if the graph contains edge types other than `-->` and `<->`,
+/// or if a cost is negative or not a number.
+pub fn find_min_cost_dsep(
+    admg: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    i: &[usize],
+    r: &[usize],
+    costs: &[f64],
+) -> Result<Option<(Vec<usize>, f64)>, ParseGraphError> {
+    check_costs(costs)?;
+    let cost = |v: usize| costs.get(v).copied().unwrap_or(1.0);
+    let ancestors_table = Ruletable::ancestors_admg();
+    let graph = Graph::new(admg, &ancestors_table)?;
+    let (x, y, i) = (to_set(x), to_set(y), to_set(i));
+    let mut r: BTreeSet<_> = r.iter().copied().filter(|&v| cost(v).is_finite()).collect();
+    r.extend(&i);
+    if nearest_dsep(&graph, &x, &y, &i, &r).is_none() {
+        return Ok(None);
+    }
+
+    let mut relevant = &x | &y;
+    relevant.extend(&i);
+    let ancestors = reach_set(&graph, &ancestors_table, &[("X", &relevant)]);
+    let nodes: Vec<_> = ancestors.difference(&i).copied().collect();
+    let index: HashMap<_, _> = nodes.iter().enumerate().map(|(k, &v)| (v, k)).collect();
+
+    // node v is split into 2k and 2k + 1 joined by an edge with its cost, all
+    // other edges have infinite capacity
+    let (source, sink) = (2 * nodes.len(), 2 * nodes.len() + 1);
+    let mut network = FlowNetwork::new(2 * nodes.len() + 2);
+    let closure_table = Ruletable::closure_admg();
+    for (k, &v) in nodes.iter().enumerate() {
+        let capacity = if r.contains(&v) && !x.contains(&v) && !y.contains(&v) {
+            cost(v)
+        } else {
+            f64::INFINITY
+        };
+        network.add_edge(2 * k, 2 * k + 1, capacity);
+        if x.contains(&v) {
+            network.add_edge(source, 2 * k, f64::INFINITY);
+        }
+        if y.contains(&v) {
+            network.add_edge(2 * k + 1, sink, f64::INFINITY);
+        }
+        let others: BTreeSet<_> = ancestors.iter().copied().filter(|&u| u != v).collect();
+        let moral_neighbors = reach_set(
+            &graph,
+            &closure_table,
+            &[("X", &to_set(&[v])), ("Z", &others), ("A", &ancestors)],
+        );
+        for u in moral_neighbors.iter().filter(|&&u| u != v) {
+            if let Some(&l) = index.get(u) {
+                network.add_edge(2 * k + 1, 2 * l, f64::INFINITY);
+            }
+        }
+    }
+
+    let reached = network.min_cut(source, sink);
+    let mut z = i;
+    z.extend(
+        nodes
+            .iter()
+            .enumerate()
+            .filter(|&(k, _)| reached[2 * k] && !reached[2 * k + 1])
+            .map(|(_, &v)| v),
+    );
+    let total = z.iter().map(|&v| cost(v)).sum();
+    Ok(Some((z.into_iter().collect(), total)))
+}
+
+// Residual network for computing maximum flows, where every edge is stored next
+// to its reverse edge.
+struct FlowNetwork {
+    edges: Vec<Vec<usize>>,
+    heads: Vec<usize>,
+    residuals: Vec<f64>,
+}
+
+impl FlowNetwork {
+    fn new(n: usize) -> Self {
+        FlowNetwork {
+            edges: vec![Vec::new(); n],
+            heads: Vec::new(),
+            residuals: Vec::new(),
+        }
+    }
+
+    fn add_edge(&mut self, u: usize, v: usize, capacity: f64) {
+        self.edges[u].push(self.heads.len());
+        self.heads.push(v);
+        self.residuals.push(capacity);
+        self.edges[v].push(self.heads.len());
+        self.heads.push(u);
+        self.residuals.push(0.0);
+    }
+
+    // Augments along shortest paths until the flow is maximum and returns the
+    // nodes reachable from the source in the residual network. The flow has to
+    // be finite. The bottleneck of each path is subtracted exactly, so saturated
+    // edges have a residual of zero despite rounding.
+    fn min_cut(&mut self, source: usize, sink: usize) -> Vec<bool> {
+        loop {
+            let mut parent_edge = vec![None; self.edges.len()];
+            let mut reached = vec![false; self.edges.len()];
+            reached[source] = true;
+            let mut queue = VecDeque::from([source]);
+            while let Some(u) = queue.pop_front() {
+                for &e in self.edges[u].iter() {
+                    let v = self.heads[e];
+                    if !reached[v] && self.residuals[e] > 0.0 {
+                        reached[v] = true;
+                        parent_edge[v] = Some(e);
+                        queue.push_back(v);
+                    }
+                }
+            }
+            if !reached[sink] {
+                return reached;
+            }
+
+            let path: Vec<_> =
+                std::iter::successors(parent_edge[sink], |&e| parent_edge[self.heads[e ^ 1]])
+                    .collect();
+            let bottleneck = path
+                .iter()
+                .map(|&e| self.residuals[e])
+                .fold(f64::INFINITY, f64::min);
+            for e in path {
+                self.residuals[e] -= bottleneck;
+                self.residuals[e ^ 1] += bottleneck;
+            }
+        }
+    }
+}
+
+/// Iterator over minimal d-separators returned by [`enumerate_minimal_separators`].
+pub struct MinimalSeparators {
+    graph: Graph,
+    closure_table: Ruletable,
+    y: BTreeSet<usize>,
+    i: BTreeSet<usize>,
+    r: BTreeSet<usize>,
+    ancestors: BTreeSet<usize>,
+    stack: Vec<Branch>,
+}
+
+// nodes on the side of x, nodes forced into the separator and the separator
+// closest to y given these constraints
+struct Branch {
+    side: BTreeSet<usize>,
+    forced: BTreeSet<usize>,
+    separator: BTreeSet<usize>,
+}
+
+impl MinimalSeparators {
+    // Returns the branch for the given constraints if it contains a minimal
+    // separator. All nodes that are not in r and adjacent to the side of x in the
+    // moral graph are moved to this side, as they cannot be in any separator.
+    fn branch(&self, side: BTreeSet<usize>, forced: BTreeSet<usize>) -> Option<Branch> {
+        let blocked = &(&self.r | &self.i) - &side;
+        let reached = self.closure(&side, &blocked);
+        let side = &reached - &blocked;
+        if !side.is_disjoint(&self.y) {
+            return None;
+        }
+
+        let neighbors = &(&reached & &blocked) - &self.i;
+        let reached = self.closure(&self.y, &(&neighbors | &self.i));
+        let separator = &reached & &neighbors;
+        if !forced.is_subset(&separator) {
+            return None;
+        }
+        Some(Branch {
+            side,
+            forced,
+            separator,
+        })
+    }
+
+    // nodes connected to x in the moral graph of the ancestors when removing z,
+    // including the nodes in z adjacent to them
+    fn closure(&self, x: &BTreeSet<usize>, z: &BTreeSet<usize>) -> BTreeSet<usize> {
+        reach_set(
+            &self.graph,
+            &self.closure_table,
+            &[("X", x), ("Z", z), ("A", &self.ancestors)],
+        )
+    }
+}
+
+impl Iterator for MinimalSeparators {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(branch) = self.stack.pop() {
+            let v = match branch.separator.difference(&branch.forced).next() {
+                Some(&v) => v,
+                None => return Some(branch.separator.union(&self.i).copied().collect()),
+            };
+
+            let mut side = branch.side.clone();
+            side.insert(v);
+            if let Some(moved) = self.branch(side, branch.forced.clone()) {
+                self.stack.push(moved);
+            }
+            let mut forced = branch.forced;
+            forced.insert(v);
+            self.stack.push(Branch { forced, ..branch });
+        }
+        None
+    }
+}
+
+pub(crate) fn nearest_dsep(
+    graph: &Graph,
+    x: &BTreeSet<usize>,
+    y: &BTreeSet<usize>,
+    i: &BTreeSet<usize>,
+    r: &BTreeSet<usize>,
+) -> Option<BTreeSet<usize>> {
+    let mut relevant = x | y;
+    relevant.extend(i);
+    let ancestors = reach_set(graph, &Ruletable::ancestors_admg(), &[("X", &relevant)]);
+    let z0 = r
+        .iter()
+        .copied()
+        .filter(|v| ancestors.contains(v) && !x.contains(v) && !y.contains(v))
+        .collect();
+    let closure = reach_set(
+        graph,
+        &Ruletable::closure_admg(),
+        &[("X", x), ("Z", &z0), ("A", &ancestors)],
+    );
+    if !closure.is_disjoint(y) {
+        return None;
+    }
+    Some(z0.intersection(&closure).chain(i.iter()).copied().collect())
+}
@@ -0,0 +1,145 @@
+//! Compact bit sets of nodes and bit matrices for relations between nodes.
+
+/// Set of nodes stored as one bit per node, as returned by [`reach_bitset`](crate::reach::reach_bitset).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitSet {
+    size: usize,
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    /// Creates an empty set that can hold the nodes below `size`.
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            words: vec![0; size.div_ceil(64)],
+        }
+    }
+
+    /// Returns the number of nodes the set can hold.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns whether node `i` is in the set, which is false for `i >= size`.
+    pub fn contains(&self, i: usize) -> bool {
+        i < self.size && self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    /// Adds node `i` to the set.
+    pub fn insert(&mut self, i: usize) {
+        assert!(i < self.size);
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    /// Returns the number of nodes in the set.
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns the nodes in the set in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(k, &word)| ones(word).map(move |b| 64 * k + b))
+    }
+}
+
+/// Matrix of bits stored row by row, as returned by [`reach_all_pairs`](crate::reach::reach_all_pairs).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitMatrix {
+    num_rows: usize,
+    num_cols: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitMatrix {
+    /// Creates a matrix with all bits unset.
+    pub fn new(num_rows: usize, num_cols: usize) -> Self {
+        let words_per_row = num_cols.div_ceil(64);
+        Self {
+            num_rows,
+            num_cols,
+            words_per_row,
+            words: vec![0; num_rows * words_per_row],
+        }
+    }
+
+    /// Returns the number of rows.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Returns the number of columns.
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    /// Returns whether the bit in row `i` and column `j` is set.
+    pub fn contains(&self, i: usize, j: usize) -> bool {
+        assert!(i < self.num_rows && j < self.num_cols);
+        self.words[i * self.words_per_row + j / 64] >> (j % 64) & 1 == 1
+    }
+
+    /// Sets the bit in row `i` and column `j`.
+    pub fn insert(&mut self, i: usize, j: usize) {
+        assert!(i < self.num_rows && j < self.num_cols);
+        self.words[i * self.words_per_row + j / 64] |= 1 << (j % 64);
+    }
+
+    /// Returns the columns of the set bits in row `i` in ascending order.
+    pub fn row(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        self.row_words(i)
+            .iter()
+            .enumerate()
+            .flat_map(|(k, &word)| ones(word).map(move |b| 64 * k + b))
+    }
+
+    /// Sets all bits of row `i` that are set in row `j` of `other`, which needs
+    /// to have the same number of columns.
+    pub fn union_row(&mut self, i: usize, other: &BitMatrix, j: usize) {
+        assert_eq!(self.num_cols, other.num_cols);
+        let start = i * self.words_per_row;
+        for (word, &other_word) in self.words[start..start + self.words_per_row]
+            .iter_mut()
+            .zip(other.row_words(j))
+        {
+            *word |= other_word;
+        }
+    }
+
+    // appends a row with all bits unset
+    pub(crate) fn push_row(&mut self) {
+        self.num_rows += 1;
+        self.words.resize(self.num_rows * self.words_per_row, 0);
+    }
+
+    // sets all bits of row i that are set in row j
+    pub(crate) fn union_own_row(&mut self, i: usize, j: usize) {
+        for k in 0..self.words_per_row {
+            let word = self.words[j * self.words_per_row + k];
+            self.words[i * self.words_per_row + k] |= word;
+        }
+    }
+
+    fn row_words(&self, i: usize) -> &[u64] {
+        assert!(i < self.num_rows);
+        &self.words[i * self.words_per_row..(i + 1) * self.words_per_row]
+    }
+}
+
+fn ones(mut word: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if word == 0 {
+            return None;
+        }
+        let b = word.trailing_zeros() as usize;
+        word &= word - 1;
+        Some(b)
+    })
+}
@@ -0,0 +1,433 @@
+//! Deciding whether two rule tables are equivalent.
+//!
+//! Rule tables are often refactored by merging or reordering rules or by renaming
+//! colors. Two rule tables over the same edge types and sets behave the same if
+//! they have the same start and output states and allow the same transitions.
+//! Whether a transition is allowed only depends on the edge types and colors of
+//! the two states and on which sets contain `current` and `next`, hence
+//! [`check_equivalence`] compares the rule tables on every such case.
+
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::{
+    expression::{Expression, MAX_VALUATION_SETS},
+    instance::Sets,
+    ruletable::{OutputSpec, Ruletable, StartSpec},
+};
+
+/// Difference between two rule tables, as returned by [`check_equivalence`].
+///
+/// Edge types, colors and sets are named as in the first rule table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference {
+    /// A start state of only one of the rule tables.
+    Start { start: StartSpec, in_first: bool },
+    /// An output state of only one of the rule tables.
+    Output { output: OutputSpec, in_first: bool },
+    /// A transition allowed by only one of the rule tables.
+    Transition {
+        prev_edge: String,
+        prev_color: Option<String>,
+        next_edge: String,
+        next_color: Option<String>,
+        /// The sets containing `current`, all other sets do not contain it.
+        current_sets: Vec<String>,
+        /// The sets containing `next`, all other sets do not contain it.
+        next_sets: Vec<String>,
+        allowed_by_first: bool,
+    },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let table = |in_first: bool| if in_first { "first" } else { "second" };
+        let state = |edge: &str, color: &Option<String>| match color {
+            Some(color) => format!("{edge} [{color}]"),
+            None => edge.to_owned(),
+        };
+        match self {
+            Difference::Start { start, in_first } => write!(
+                f,
+                "start state {} at {} only in {} rule table",
+                state(&start.edge, &start.color),
+                start.set,
+                table(*in_first)
+            ),
+            Difference::Output { output, in_first } => write!(
+                f,
+                "output state {} only in {} rule table",
+                state(&output.edge, &output.color),
+                table(*in_first)
+            ),
+            Difference::Transition {
+                prev_edge,
+                prev_color,
+                next_edge,
+                next_color,
+                current_sets,
+                next_sets,
+                allowed_by_first,
+            } => write!(
+                f,
+                "transition {} | {} with current in {{{}}} and next in {{{}}} only allowed by {} rule table",
+                state(prev_edge, prev_color),
+                state(next_edge, next_color),
+                current_sets.join(", "),
+                next_sets.join(", "),
+                table(*allowed_by_first)
+            ),
+        }
+    }
+}
+
+/// Checks whether two rule tables allow the same walks in every graph.
+///
+/// The rule tables have to declare the same edge types, with the same reverse
+/// edge types, and the same sets, in any order. Colors may be named and ordered
+/// differently: the rule tables are equivalent if some bijection between their
+/// colors makes them equivalent, which is searched by backtracking over the
+/// colors of the first rule table.
+///
+/// Returns `None` if the rule tables have the same start and output states and
+/// allow the same transitions for all edge types and colors of the previous and
+/// next state and all combinations of sets containing `current` and `next`.
+/// Otherwise, returns the first difference found, comparing start and output
+/// states before transitions, with colors matched by name if both rule tables
+/// declare the same colors and by declaration order otherwise. The number of
+/// combinations of sets that are checked is exponential in the number of sets
+/// referred to by the two rules deciding a transition, and the number of
+/// bijections tried is at worst factorial in the number of colors.
+///
+/// # Errors
+/// Returns an error if the edge types, sets or number of colors differ, or if a
+/// transition depends on more than 10 sets.
+pub fn check_equivalence(
+    first: &Ruletable,
+    second: &Ruletable,
+) -> Result<Option<Difference>, IncompatibleRuletablesError> {
+    let edges = first.edge_names();
+    let mut second_edges = second.edge_names();
+    second_edges.sort();
+    let mut sorted_edges = edges.clone();
+    sorted_edges.sort();
+    if sorted_edges != second_edges
+        || edges
+            .iter()
+            .any(|e| first.reverse_edge_name(e) != second.reverse_edge_name(e))
+    {
+        return Err(IncompatibleRuletablesError(format!(
+            "edge types differ: {} and {}",
+            edges.join(", "),
+            second.edge_names().join(", ")
+        )));
+    }
+    let set_names = first.set_names();
+    let mut sorted_sets = set_names.clone();
+    sorted_sets.sort();
+    let mut second_sets = second.set_names();
+    second_sets.sort();
+    if sorted_sets != second_sets {
+        return Err(IncompatibleRuletablesError(format!(
+            "sets differ: {} and {}",
+            set_names.join(", "),
+            second.set_names().join(", ")
+        )));
+    }
+    let colors = first.color_names();
+    let second_colors = second.color_names();
+    if colors.len() != second_colors.len() {
+        return Err(IncompatibleRuletablesError(format!(
+            "number of colors differs: {} and {}",
+            colors.len(),
+            second_colors.len()
+        )));
+    }
+
+    let mut sorted_colors = colors.clone();
+    sorted_colors.sort();
+    let mut second_sorted_colors = second_colors.clone();
+    second_sorted_colors.sort();
+    let preferred: Vec<_> = if !colors.is_empty() && sorted_colors == second_sorted_colors {
+        colors
+            .iter()
+            .map(|c| second.get_color_id(c).expect("color should exist"))
+            .collect()
+    } else {
+        (0..first.num_colors()).collect()
+    };
+    let comparison = Comparison {
+        first,
+        second,
+        edges: &edges,
+        set_names: &set_names,
+        edge_map: edges
+            .iter()
+            .map(|e| second.get_edge_ids(e).expect("edge should exist").0)
+            .collect(),
+        set_map: set_names
+            .iter()
+            .map(|s| second.get_set_id(s).expect("set should exist"))
+            .collect(),
+        first_starts: first.start_specs(),
+        second_starts: second.start_specs(),
+        first_outputs: first.output_specs(),
+        second_outputs: second.output_specs(),
+    };
+    if comparison.find_bijection(&preferred, &mut Vec::new())? {
+        Ok(None)
+    } else {
+        comparison.difference(&preferred, None)
+    }
+}
+
+// The ids of the second rule table for the edges and sets of the first one,
+// together with the start and output states of both.
+struct Comparison<'a> {
+    first: &'a Ruletable,
+    second: &'a Ruletable,
+    edges: &'a [String],
+    set_names: &'a [String],
+    edge_map: Vec<usize>,
+    set_map: Vec<usize>,
+    first_starts: Vec<StartSpec>,
+    second_starts: Vec<StartSpec>,
+    first_outputs: Vec<OutputSpec>,
+    second_outputs: Vec<OutputSpec>,
+}
+
+impl Comparison<'_> {
+    // Extends the color map, which maps colors of the first rule table to those of
+    // the second one, to a bijection without differences. Each color is mapped to
+    // its preferred color first and only the states and transitions involving the
+    // newly mapped color are compared, so partial maps with a difference are pruned.
+    fn find_bijection(
+        &self,
+        preferred: &[usize],
+        color_map: &mut Vec<usize>,
+    ) -> Result<bool, IncompatibleRuletablesError> {
+        let c = color_map.len();
+        if c == preferred.len() {
+            return Ok(true);
+        }
+        let candidates = std::iter::once(preferred[c])
+            .chain((0..preferred.len()).filter(|&d| d != preferred[c]));
+        for d in candidates {
+            if color_map.contains(&d) {
+                continue;
+            }
+            color_map.push(d);
+            if self.difference(color_map, Some(c))?.is_none()
+                && self.find_bijection(preferred, color_map)?
+            {
+                return Ok(true);
+            }
+            color_map.pop();
+        }
+        Ok(false)
+    }
+
+    // Returns the first difference under the color map, only considering states
+    // and transitions whose largest color of the first rule table is `level` if set.
+    fn difference(
+        &self,
+        color_map: &[usize],
+        level: Option<usize>,
+    ) -> Result<Option<Difference>, IncompatibleRuletablesError> {
+        let (first, second) = (self.first, self.second);
+        let is_at_level = |c: usize| level.unwrap_or(c) == c;
+        let first_id = |c: &Option<String>| {
+            c.as_ref()
+                .map_or(0, |c| first.get_color_id(c).expect("color should exist"))
+        };
+        let second_id = |c: &Option<String>| {
+            c.as_ref()
+                .map_or(0, |c| second.get_color_id(c).expect("color should exist"))
+        };
+        let first_name = |c: usize| first.color_names().get(c).cloned();
+        let second_name = |c: usize| second.color_names().get(c).cloned();
+        let from_second = |c: &Option<String>| {
+            let id = second_id(c);
+            color_map.iter().position(|&d| d == id).map(first_name)
+        };
+
+        // states of the second rule table are named as in the first one, states
+        // whose color is not mapped yet are skipped
+        for start in self.first_starts.iter() {
+            let c = first_id(&start.color);
+            if !is_at_level(c) {
+                continue;
+            }
+            let translated = StartSpec {
+                color: second_name(color_map[c]),
+                ..start.clone()
+            };
+            if !self.second_starts.contains(&translated) {
+                return Ok(Some(Difference::Start {
+                    start: start.clone(),
+                    in_first: true,
+                }));
+            }
+        }
+        for start in self.second_starts.iter() {
+            if let Some(color) = from_second(&start.color) {
+                let translated = StartSpec {
+                    color,
+                    ..start.clone()
+                };
+                if is_at_level(first_id(&translated.color))
+                    && !self.first_starts.contains(&translated)
+                {
+                    return Ok(Some(Difference::Start {
+                        start: translated,
+                        in_first: false,
+                    }));
+                }
+            }
+        }
+        for output in self.first_outputs.iter() {
+            let c = first_id(&output.color);
+            if !is_at_level(c) {
+                continue;
+            }
+            let translated = OutputSpec {
+                color: second_name(color_map[c]),
+                ..output.clone()
+            };
+            if !self.second_outputs.contains(&translated) {
+                return Ok(Some(Difference::Output {
+                    output: output.clone(),
+                    in_first: true,
+                }));
+            }
+        }
+        for output in self.second_outputs.iter() {
+            if let Some(color) = from_second(&output.color) {
+                let translated = OutputSpec {
+                    color,
+                    ..output.clone()
+                };
+                if is_at_level(first_id(&translated.color))
+                    && !self.first_outputs.contains(&translated)
+                {
+                    return Ok(Some(Difference::Output {
+                        output: translated,
+                        in_first: false,
+                    }));
+                }
+            }
+        }
+
+        for e1 in 0..first.num_edges() {
+            for c1 in 0..color_map.len() {
+                for e2 in 0..first.num_edges() {
+                    for c2 in 0..color_map.len() {
+                        if !is_at_level(c1.max(c2)) {
+                            continue;
+                        }
+                        let first_expression = first.rule_expression(e1, c1, e2, c2);
+                        let second_expression = second.rule_expression(
+                            self.edge_map[e1],
+                            color_map[c1],
+                            self.edge_map[e2],
+                            color_map[c2],
+                        );
+                        let valuation = find_valuation(
+                            (first, first_expression),
+                            (second, second_expression),
+                            &self.set_map,
+                        )
+                        .map_err(|num_sets| {
+                            IncompatibleRuletablesError(format!(
+                                "transition {} | {} depends on {num_sets} sets, at most {MAX_VALUATION_SETS} are supported",
+                                self.edges[e1], self.edges[e2]
+                            ))
+                        })?;
+                        if let Some((current, next, allowed_by_first)) = valuation {
+                            let names = |members: Vec<usize>| {
+                                members
+                                    .into_iter()
+                                    .map(|s| self.set_names[s].clone())
+                                    .collect()
+                            };
+                            return Ok(Some(Difference::Transition {
+                                prev_edge: self.edges[e1].clone(),
+                                prev_color: first_name(c1),
+                                next_edge: self.edges[e2].clone(),
+                                next_color: first_name(c2),
+                                current_sets: names(current),
+                                next_sets: names(next),
+                                allowed_by_first,
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+// sets containing current, sets containing next and whether the first rule table
+// allows the transition
+type Valuation = (Vec<usize>, Vec<usize>, bool);
+
+// Searches for sets containing current and next, given by set ids of the first
+// rule table, for which exactly one of the expressions is true. A missing
+// expression means that no rule matches and the transition is never allowed.
+// Returns the number of sets as error if there are more than MAX_VALUATION_SETS.
+fn find_valuation(
+    (first, first_expression): (&Ruletable, Option<&Expression>),
+    (second, second_expression): (&Ruletable, Option<&Expression>),
+    set_map: &[usize],
+) -> Result<Option<Valuation>, usize> {
+    let used: Vec<_> = (0..set_map.len())
+        .filter(|&s| {
+            first_expression.is_some_and(|e| e.uses_set(s))
+                || second_expression.is_some_and(|e| e.uses_set(set_map[s]))
+        })
+        .collect();
+    if used.len() > MAX_VALUATION_SETS {
+        return Err(used.len());
+    }
+    let empty = HashMap::new();
+    let mut first_sets = Sets::new(&empty, first).expect("empty sets should be valid");
+    let mut second_sets = Sets::new(&empty, second).expect("empty sets should be valid");
+    // node 0 stands for current and node 1 for next
+    for mask in 0..(1_u64 << (2 * used.len())) {
+        let (mut current, mut next) = (Vec::new(), Vec::new());
+        for (i, &s) in used.iter().enumerate() {
+            for (node, members) in [(0, &mut current), (1, &mut next)] {
+                if mask >> (2 * i + node) & 1 == 1 {
+                    first_sets.insert(s, node);
+                    second_sets.insert(set_map[s], node);
+                    members.push(s);
+                } else {
+                    first_sets.remove(s, node);
+                    second_sets.remove(set_map[s], node);
+                }
+            }
+        }
+        let allowed_by_first = first_expression.is_some_and(|e| e.evaluate(&first_sets, 0, 1));
+        let allowed_by_second = second_expression.is_some_and(|e| e.evaluate(&second_sets, 0, 1));
+        if allowed_by_first != allowed_by_second {
+            return Ok(Some((current, next, allowed_by_first)));
+        }
+    }
+    Ok(None)
+}
+
+/// Error type for reporting rule tables that cannot be compared.
+#[derive(Debug)]
+pub struct IncompatibleRuletablesError(String);
+
+impl fmt::Display for IncompatibleRuletablesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Error when comparing rule tables: {}", self.0)
+    }
+}
+
+impl Error for IncompatibleRuletablesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,326 @@
+//! Symbolic estimands of causal effects.
+//!
+//! An [`Estimand`] is an expression tree of sums, products and quotients of
+//! conditional probabilities that identifies a causal effect from the
+//! observational distribution. The functions in this module build the estimands
+//! for the sets found by the [`algorithms`](crate::algorithms) module, which can
+//! then be rendered as plain text or LaTeX. Nodes are named by their position in
+//! a slice of names, nodes without a name are written as their id.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
+
+/// Symbolic expression in terms of the observational distribution over the nodes of a graph.
+///
+/// Sums bind the nodes they range over. When rendered, a bound node that is also
+/// used outside of the sum is marked with a prime, as in the front-door formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Estimand {
+    /// The probability `P(vars | given)`, which is a marginal if `given` is empty.
+    Probability { vars: Vec<usize>, given: Vec<usize> },
+    /// The sum of `term` over all values of the nodes in `over`.
+    Sum {
+        over: Vec<usize>,
+        term: Box<Estimand>,
+    },
+    /// The product of all factors.
+    Product(Vec<Estimand>),
+    /// The quotient of a numerator and a denominator.
+    Quotient(Box<Estimand>, Box<Estimand>),
+    /// The covariance `Cov(a, b | given)`, used for estimands in linear models.
+    Covariance {
+        a: Vec<usize>,
+        b: Vec<usize>,
+        given: Vec<usize>,
+    },
+    /// The inverse of a covariance matrix, used for estimands with several instruments.
+    Inverse(Box<Estimand>),
+}
+
+/// Returns the estimand `sum_z P(y | x, z) P(z)` of the effect of `x` on `y`
+/// for an adjustment set `z`, which is `P(y | x)` if `z` is empty.
+pub fn adjustment(x: &[usize], y: &[usize], z: &[usize]) -> Estimand {
+    let z = to_set(z);
+    let factors = vec![
+        Estimand::probability(&to_set(y), &(&to_set(x) | &z)),
+        Estimand::probability(&z, &BTreeSet::new()),
+    ];
+    Estimand::sum(&z, Estimand::product(factors))
+}
+
+/// Returns the front-door estimand `sum_z P(z | x) sum_x' P(y | x', z) P(x')` of
+/// the effect of `x` on `y` for a front-door adjustment set `z`.
+pub fn frontdoor(x: &[usize], y: &[usize], z: &[usize]) -> Estimand {
+    let (x, z) = (to_set(x), to_set(z));
+    let outcome = Estimand::sum(
+        &x,
+        Estimand::product(vec![
+            Estimand::probability(&to_set(y), &(&x | &z)),
+            Estimand::probability(&x, &BTreeSet::new()),
+        ]),
+    );
+    Estimand::sum(
+        &z,
+        Estimand::product(vec![Estimand::probability(&z, &x), outcome]),
+    )
+}
+
+/// Returns the estimand of the effect of `x` on `y` in a linear model for the
+/// conditional instruments `z` given `w`, as found by
+/// [`optimal_instrument`](crate::algorithms::optimal_instrument).
+///
+/// This is the two-stage least squares estimand
+/// `Cov(x, z | w) Cov(z, z | w)^-1 Cov(z, y | w) / (Cov(x, z | w) Cov(z, z | w)^-1 Cov(z, x | w))`,
+/// where covariances of several nodes are matrices. For a single instrument it
+/// simplifies to `Cov(y, z | w) / Cov(x, z | w)`, which is returned instead.
+pub fn instrument(x: usize, y: usize, z: &[usize], w: &[usize]) -> Estimand {
+    let z: Vec<_> = to_set(z).into_iter().collect();
+    let given: Vec<_> = to_set(w).into_iter().collect();
+    let covariance = |a: &[usize], b: &[usize]| Estimand::Covariance {
+        a: a.to_vec(),
+        b: b.to_vec(),
+        given: given.clone(),
+    };
+    if z.len() == 1 {
+        return Estimand::Quotient(
+            Box::new(covariance(&[y], &z)),
+            Box::new(covariance(&[x], &z)),
+        );
+    }
+    let weighted = |a: usize| {
+        Estimand::Product(vec![
+            covariance(&[x], &z),
+            Estimand::Inverse(Box::new(covariance(&z, &z))),
+            covariance(&z, &[a]),
+        ])
+    };
+    Estimand::Quotient(Box::new(weighted(y)), Box::new(weighted(x)))
+}
+
+impl Estimand {
+    pub(crate) fn probability(vars: &BTreeSet<usize>, given: &BTreeSet<usize>) -> Estimand {
+        Estimand::Probability {
+            vars: vars.iter().copied().collect(),
+            given: given.iter().copied().collect(),
+        }
+    }
+
+    // sums out the nodes in over, marginalizing probabilities directly
+    pub(crate) fn sum(over: &BTreeSet<usize>, term: Estimand) -> Estimand {
+        match term {
+            _ if over.is_empty() => term,
+            Estimand::Probability { vars, given }
+                if over.iter().all(|v| vars.contains(v) && !given.contains(v)) =>
+            {
+                Estimand::Probability {
+                    vars: vars.into_iter().filter(|v| !over.contains(v)).collect(),
+                    given,
+                }
+            }
+            Estimand::Sum { over: inner, term } => {
+                let over: BTreeSet<_> = over.iter().chain(inner.iter()).copied().collect();
+                Estimand::Sum {
+                    over: over.into_iter().collect(),
+                    term,
+                }
+            }
+            _ => Estimand::Sum {
+                over: over.iter().copied().collect(),
+                term: Box::new(term),
+            },
+        }
+    }
+
+    // drops empty probabilities and flattens products
+    pub(crate) fn product(factors: Vec<Estimand>) -> Estimand {
+        let mut flattened = Vec::new();
+        for factor in factors {
+            match factor {
+                Estimand::Probability { vars, .. } if vars.is_empty() => {}
+                Estimand::Product(inner) => flattened.extend(inner),
+                _ => flattened.push(factor),
+            }
+        }
+        if flattened.len() == 1 {
+            flattened.pop().unwrap()
+        } else {
+            Estimand::Product(flattened)
+        }
+    }
+
+    /// Returns the nodes that occur in the estimand without being bound by a sum.
+    pub fn free_nodes(&self) -> BTreeSet<usize> {
+        match self {
+            Estimand::Probability { vars, given } => vars.iter().chain(given).copied().collect(),
+            Estimand::Sum { over, term } => {
+                let mut free = term.free_nodes();
+                free.retain(|v| !over.contains(v));
+                free
+            }
+            Estimand::Product(factors) => factors.iter().flat_map(Estimand::free_nodes).collect(),
+            Estimand::Quotient(numerator, denominator) => {
+                &numerator.free_nodes() | &denominator.free_nodes()
+            }
+            Estimand::Covariance { a, b, given } => {
+                a.iter().chain(b).chain(given).copied().collect()
+            }
+            Estimand::Inverse(inner) => inner.free_nodes(),
+        }
+    }
+
+    /// Renders the estimand as plain text, for example `sum_{z} [P(y | x, z) P(z)]`.
+    ///
+    /// Node `v` is written as `names[v]`, or as `v` if there is no such name.
+    pub fn to_text(&self, names: &[&str]) -> String {
+        let mut renderer = Renderer::new(self, names, false);
+        renderer.render(self, false);
+        renderer.output
+    }
+
+    /// Renders the estimand as LaTeX, for example `\sum_{z} P(y \mid x, z) P(z)`.
+    ///
+    /// Node `v` is written as `names[v]`, or as `v` if there is no such name. Names
+    /// are inserted verbatim, so they may contain LaTeX commands.
+    pub fn to_latex(&self, names: &[&str]) -> String {
+        let mut renderer = Renderer::new(self, names, true);
+        renderer.render(self, false);
+        renderer.output
+    }
+}
+
+impl fmt::Display for Estimand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_text(&[]))
+    }
+}
+
+fn to_set(nodes: &[usize]) -> BTreeSet<usize> {
+    nodes.iter().copied().collect()
+}
+
+struct Renderer<'a> {
+    names: &'a [&'a str],
+    latex: bool,
+    // nodes in scope and the number of primes of their current binding
+    scope: HashMap<usize, usize>,
+    output: String,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(estimand: &Estimand, names: &'a [&'a str], latex: bool) -> Renderer<'a> {
+        Renderer {
+            names,
+            latex,
+            scope: estimand.free_nodes().into_iter().map(|v| (v, 0)).collect(),
+            output: String::new(),
+        }
+    }
+
+    fn nodes(&mut self, nodes: &[usize]) {
+        for (i, v) in nodes.iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(", ");
+            }
+            match self.names.get(*v) {
+                Some(name) => self.output.push_str(name),
+                None => self.output.push_str(&v.to_string()),
+            }
+            let primes = self.scope.get(v).copied().unwrap_or_default();
+            self.output.push_str(&"'".repeat(primes));
+        }
+    }
+
+    fn conditional(&mut self, function: &str, args: &[&[usize]], given: &[usize]) {
+        self.output.push_str(function);
+        self.output.push('(');
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(", ");
+            }
+            self.nodes(arg);
+        }
+        if !given.is_empty() {
+            self.output
+                .push_str(if self.latex { " \\mid " } else { " | " });
+            self.nodes(given);
+        }
+        self.output.push(')');
+    }
+
+    // factors other than the last one are enclosed in brackets if they are sums
+    fn render(&mut self, estimand: &Estimand, enclose: bool) {
+        match estimand {
+            Estimand::Probability { vars, given } => self.conditional("P", &[vars], given),
+            Estimand::Covariance { a, b, given } => {
+                let function = if self.latex {
+                    "\\operatorname{Cov}"
+                } else {
+                    "Cov"
+                };
+                self.conditional(function, &[a, b], given)
+            }
+            Estimand::Inverse(inner) => {
+                self.render(inner, false);
+                self.output
+                    .push_str(if self.latex { "^{-1}" } else { "^-1" });
+            }
+            Estimand::Sum { over, term } => {
+                let shadowed: Vec<_> = over
+                    .iter()
+                    .map(|&v| (v, self.scope.get(&v).copied()))
+                    .collect();
+                for &(v, primes) in shadowed.iter() {
+                    self.scope.insert(v, primes.map_or(0, |p| p + 1));
+                }
+                self.output
+                    .push_str(if self.latex { "\\sum_{" } else { "sum_{" });
+                self.nodes(over);
+                if self.latex {
+                    self.output.push_str("} ");
+                    if enclose {
+                        self.output.push_str("\\left[");
+                    }
+                    self.render(term, false);
+                    if enclose {
+                        self.output.push_str("\\right]");
+                    }
+                } else {
+                    self.output.push_str("} [");
+                    self.render(term, false);
+                    self.output.push(']');
+                }
+                for (v, primes) in shadowed {
+                    match primes {
+                        Some(p) => self.scope.insert(v, p),
+                        None => self.scope.remove(&v),
+                    };
+                }
+            }
+            Estimand::Product(factors) => {
+                for (i, factor) in factors.iter().enumerate() {
+                    if i > 0 {
+                        self.output.push(' ');
+                    }
+                    self.render(factor, enclose || i + 1 < factors.len());
+                }
+            }
+            Estimand::Quotient(numerator, denominator) => {
+                if self.latex {
+                    self.output.push_str("\\frac{");
+                    self.render(numerator, false);
+                    self.output.push_str("}{");
+                    self.render(denominator, false);
+                    self.output.push('}');
+                } else {
+                    self.output.push('(');
+                    self.render(numerator, false);
+                    self.output.push_str(") / (");
+                    self.render(denominator, false);
+                    self.output.push(')');
+                }
+            }
+        }
+    }
+}
@@ -1,3 +1,10 @@
+//! Parsed expressions of rule table rules.
+//!
+//! Expressions are returned by
+//! [`Ruletable::rule_specs`](crate::Ruletable::rule_specs) for inspecting a rule
+//! table. Sets are referred to by their id, which is their position in
+//! [`Ruletable::set_names`](crate::Ruletable::set_names).
+
 use std::{collections::HashMap, error::Error, fmt};
 
 use crate::instance::Sets;
@@ -5,7 +12,13 @@ use crate::instance::Sets;
 // used pratt parser for expression parsing, inspired by:
 // https://matklad.github.io/2020/04/13/simple-but-powerful-pratt-parsing.html
 
-pub(crate) enum Expression {
+/// Boolean expression deciding whether a transition is allowed.
+///
+/// The membership operators `in` and `not in` have the variable `current` or
+/// `next` as their first operand and a set as their second operand. The operators
+/// `and` and `or` are binary and `not` is unary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
     Atom(RuletableAtom),
     Junction(Op, Vec<Expression>),
 }
@@ -25,7 +38,9 @@ impl fmt::Display for Expression {
     }
 }
 
-pub(crate) enum RuletableAtom {
+/// Constants, variables and sets occurring in expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuletableAtom {
     True,
     False,
     Current,
@@ -66,7 +81,7 @@ impl RuletableAtom {
         match sets.get(atom) {
             Some(&a) => Ok(RuletableAtom::Set(a)),
             None => Err(ParseExpressionError(format!(
-                "could not find set '{}', are you sure you defined it?",
+                "could not find set or definition '{}', are you sure you defined it?",
                 atom
             ))),
         }
@@ -87,8 +102,9 @@ impl fmt::Display for RuletableAtom {
     }
 }
 
+/// Operators occurring in expressions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum Op {
+pub enum Op {
     And,
     In,
     Not,
@@ -123,17 +139,33 @@ impl Error for ParseExpressionError {
     }
 }
 
+// Largest number of sets expressions are compared over, as checking all 4^n
+// combinations of sets containing current and next takes too long beyond this.
+pub(crate) const MAX_VALUATION_SETS: usize = 10;
+
 impl Expression {
     pub(crate) fn from_string(
         input: &str,
         sets: &HashMap<String, usize>,
+        definitions: &HashMap<String, String>,
     ) -> Result<Expression, ParseExpressionError> {
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(input, definitions)?;
         let expression = Self::expr_bp(&mut lexer, 0, sets)?;
         expression.check()?;
         Ok(expression)
     }
 
+    pub(crate) fn is_keyword(s: &str) -> bool {
+        let atoms = [
+            RuletableAtom::True,
+            RuletableAtom::False,
+            RuletableAtom::Current,
+            RuletableAtom::Next,
+        ];
+        atoms.iter().any(|a| a.get_identifier() == Some(s))
+            || matches!(Lexer::to_token(s), Token::Op(_))
+    }
+
     fn expr_bp(
         lexer: &mut Lexer,
         min_bp: u8,
@@ -291,6 +323,37 @@ impl Expression {
         }
     }
 
+    /// Formats the expression in rule table syntax, naming set `i` as `set_names[i]`.
+    ///
+    /// Nested conjunctions and disjunctions are enclosed in parentheses, so the
+    /// result parses to the same expression.
+    pub fn to_ruletable_string(&self, set_names: &[String]) -> String {
+        let nested = |e: &Expression| match e {
+            Expression::Junction(Op::And | Op::Or, _) => {
+                format!("({})", e.to_ruletable_string(set_names))
+            }
+            _ => e.to_ruletable_string(set_names),
+        };
+        match self {
+            Expression::Atom(RuletableAtom::Set(s)) => set_names[*s].clone(),
+            Expression::Atom(a) => a.to_string(),
+            Expression::Junction(Op::Not, es) => format!("not {}", nested(&es[0])),
+            Expression::Junction(op, es) => es
+                .iter()
+                .map(nested)
+                .collect::<Vec<_>>()
+                .join(&format!(" {op} ")),
+        }
+    }
+
+    pub(crate) fn uses_set(&self, set_id: usize) -> bool {
+        match self {
+            Expression::Atom(RuletableAtom::Set(s)) => *s == set_id,
+            Expression::Atom(_) => false,
+            Expression::Junction(_, es) => es.iter().any(|e| e.uses_set(set_id)),
+        }
+    }
+
     pub(crate) fn evaluate(&self, sets: &Sets, v1: usize, v2: usize) -> bool {
         match self {
             Expression::Atom(a) => match a {
@@ -364,10 +427,26 @@ impl Expression {
 fn test_parser() {
     let input = "current in Z and next in W";
     let sets = vec![("Z".to_owned(), 0), ("W".to_owned(), 1)];
-    let s = Expression::from_string(input, &HashMap::from_iter(sets.into_iter())).unwrap();
+    let s = Expression::from_string(input, &HashMap::from_iter(sets), &HashMap::new()).unwrap();
     assert_eq!(s.to_string(), "(and (in current 0) (in next 1))");
 }
 
+#[test]
+fn test_parser_definitions() {
+    let sets = HashMap::from_iter(vec![("Z".to_owned(), 0), ("W".to_owned(), 1)]);
+    let mut definitions = HashMap::new();
+    definitions.insert("open".to_owned(), "current not in Z".to_owned());
+    definitions.insert("guard".to_owned(), "open and next in W".to_owned());
+    let s = Expression::from_string("not guard or true", &sets, &definitions).unwrap();
+    assert_eq!(
+        s.to_string(),
+        "(or (not (and (not in current 0) (in next 1))) true)"
+    );
+
+    definitions.insert("open".to_owned(), "guard or current in Z".to_owned());
+    assert!(Expression::from_string("guard", &sets, &definitions).is_err());
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Token {
     Atom(String),
@@ -395,7 +474,49 @@ struct Lexer {
 }
 
 impl Lexer {
-    fn new(input: &str) -> Lexer {
+    fn new(
+        input: &str,
+        definitions: &HashMap<String, String>,
+    ) -> Result<Lexer, ParseExpressionError> {
+        let mut tokens = Self::expand(Self::tokenize(input), definitions, &mut Vec::new())?;
+        tokens.reverse();
+        Ok(Lexer { tokens })
+    }
+
+    // replaces every defined name by its braced definition, the stack of names
+    // currently being expanded is used to detect cyclic definitions
+    fn expand(
+        tokens: Vec<Token>,
+        definitions: &HashMap<String, String>,
+        stack: &mut Vec<String>,
+    ) -> Result<Vec<Token>, ParseExpressionError> {
+        let mut expanded = Vec::new();
+        for t in tokens {
+            let name = match t {
+                Token::Atom(ref name) if definitions.contains_key(name) => name.clone(),
+                _ => {
+                    expanded.push(t);
+                    continue;
+                }
+            };
+            if let Some(pos) = stack.iter().position(|s| *s == name) {
+                return Err(ParseExpressionError(format!(
+                    "found cyclic definition {} -> {}",
+                    stack[pos..].join(" -> "),
+                    name
+                )));
+            }
+            stack.push(name.clone());
+            let body = Self::tokenize(&definitions[&name]);
+            expanded.push(Token::BraceOpen);
+            expanded.append(&mut Self::expand(body, definitions, stack)?);
+            expanded.push(Token::BraceClose);
+            stack.pop();
+        }
+        Ok(expanded)
+    }
+
+    fn tokenize(input: &str) -> Vec<Token> {
         let mut tokens = Vec::new();
         let mut currently_parsing = false;
         let mut current_token = "".to_owned();
@@ -450,11 +571,7 @@ impl Lexer {
                 processed_tokens.push(t.clone());
             }
         }
-        processed_tokens.reverse();
-
-        Lexer {
-            tokens: processed_tokens,
-        }
+        processed_tokens
     }
 
     fn to_token(token: &str) -> Token {
@@ -478,7 +595,7 @@ impl Lexer {
 #[test]
 fn test_lexer() {
     let s = "current in C and not (current not in X or next not in V) or next in ancestors_old";
-    let mut lexer = Lexer::new(s);
+    let mut lexer = Lexer::new(s, &HashMap::new()).unwrap();
     assert_eq!(lexer.next(), Token::Atom("current".to_owned()));
     assert_eq!(lexer.next(), Token::Op(Op::In));
     assert_eq!(lexer.next(), Token::Atom("C".to_owned()));
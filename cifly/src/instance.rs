@@ -4,6 +4,14 @@ use std::{cmp, fmt};
 
 use crate::ruletable::Ruletable;
 
+// edge type with its edges and their weights if the graph is weighted
+type EdgeList<'a> = (&'a str, &'a [(usize, usize)], Option<&'a [f64]>);
+
+// weights have to be non-negative numbers for the Dijkstra search
+pub(crate) fn is_valid_weight(weight: f64) -> bool {
+    weight >= 0.0
+}
+
 /// Internal `Graph` representation.
 ///
 /// # Usage
@@ -12,6 +20,9 @@ pub struct Graph {
     n: usize,
     seps: Vec<usize>,
     vals: Vec<(usize, usize)>,
+    // weights aligned with vals, empty for unweighted graphs
+    weights: Vec<f64>,
+    node_weights: Vec<f64>,
 }
 
 impl Graph {
@@ -20,13 +31,79 @@ impl Graph {
     /// Each edge type (string) must be defined in the `Ruletable`.
     /// Each entry in `edge_lists` is a list of edges (u, v).
     ///
+    /// The construction is deterministic: the neighbors of every node are stored
+    /// by edge type in ascending order of the edge type strings and, for each edge
+    /// type, in the order of the edge list. Hence, the order in which `reach`
+    /// returns nodes does not depend on the iteration order of `edge_lists`.
+    ///
     /// Returns an error if any edge type is undefined in the rule table.
     pub fn new(
         edge_lists: &HashMap<String, Vec<(usize, usize)>>,
         ruletable: &Ruletable,
     ) -> Result<Graph, ParseGraphError> {
+        let lists = edge_lists
+            .iter()
+            .map(|(edge_string, edges)| (edge_string.as_str(), edges.as_slice(), None))
+            .collect();
+        Self::build(lists, ruletable)
+    }
+
+    /// Constructs a new `Graph` from a map of edge types to lists of weighted edges.
+    ///
+    /// Each entry in `edge_lists` is a list of edges (u, v, weight), the weights are
+    /// used by [`reach_weighted`](crate::reach::reach_weighted). The neighbors are
+    /// stored in the same order as for [`Graph::new`].
+    ///
+    /// Returns an error if any edge type is undefined in the rule table or if a
+    /// weight is negative or not a number.
+    pub fn new_weighted(
+        edge_lists: &HashMap<String, Vec<(usize, usize, f64)>>,
+        ruletable: &Ruletable,
+    ) -> Result<Graph, ParseGraphError> {
+        let mut split = Vec::new();
+        for (edge_string, edges) in edge_lists.iter() {
+            if let Some(&(u, v, w)) = edges.iter().find(|&&(_, _, w)| !is_valid_weight(w)) {
+                return Err(ParseGraphError(format!(
+                    "edge ({u}, {v}) of type {edge_string} has invalid weight {w}"
+                )));
+            }
+            let pairs: Vec<_> = edges.iter().map(|&(u, v, _)| (u, v)).collect();
+            let weights: Vec<_> = edges.iter().map(|&(_, _, w)| w).collect();
+            split.push((edge_string.as_str(), pairs, weights));
+        }
+        let lists = split
+            .iter()
+            .map(|(edge_string, pairs, weights)| {
+                (*edge_string, pairs.as_slice(), Some(weights.as_slice()))
+            })
+            .collect();
+        Self::build(lists, ruletable)
+    }
+
+    /// Sets the weight of every node, nodes without a weight have weight zero.
+    ///
+    /// Node weights are used by [`reach_weighted`](crate::reach::reach_weighted).
+    /// Returns an error if a weight is negative or not a number.
+    pub fn set_node_weights(&mut self, weights: &[f64]) -> Result<(), ParseGraphError> {
+        if let Some((v, w)) = weights
+            .iter()
+            .enumerate()
+            .find(|&(_, &w)| !is_valid_weight(w))
+        {
+            return Err(ParseGraphError(format!("node {v} has invalid weight {w}")));
+        }
+        self.node_weights = weights.to_vec();
+        Ok(())
+    }
+
+    fn build(
+        mut edge_lists: Vec<EdgeList>,
+        ruletable: &Ruletable,
+    ) -> Result<Graph, ParseGraphError> {
+        // the order of the neighbors may not depend on the iteration order of the map
+        edge_lists.sort_by_key(|&(edge_string, _, _)| edge_string);
         let mut n = 0;
-        for edges in edge_lists.values() {
+        for (_, edges, _) in edge_lists.iter() {
             for &(u, v) in edges.iter() {
                 n = cmp::max(n, cmp::max(u, v));
             }
@@ -34,7 +111,7 @@ impl Graph {
         n += 1;
 
         let mut degree = vec![0_usize; n];
-        for edges in edge_lists.values() {
+        for (_, edges, _) in edge_lists.iter() {
             for &(u, v) in edges.iter() {
                 degree[u] += 1;
                 degree[v] += 1;
@@ -47,17 +124,23 @@ impl Graph {
         }
 
         let mut vals = vec![(0_usize, 0_usize); seps[n]];
+        let is_weighted = edge_lists.iter().any(|(_, _, weights)| weights.is_some());
+        let mut weights = if is_weighted {
+            vec![0.0; seps[n]]
+        } else {
+            Vec::new()
+        };
         let mut cursor = seps[..n].to_vec();
 
-        for (edge_string, edges) in edge_lists.iter() {
-            let (edge_num, edge_rev_num) = ruletable
-                .get_edge_ids(edge_string)
-                .ok_or(ParseGraphError(format!(
-                    "edge {edge_string} was not specified in rule table"
-                )))
-                .unwrap();
+        for (edge_string, edges, edge_weights) in edge_lists.into_iter() {
+            let (edge_num, edge_rev_num) =
+                ruletable
+                    .get_edge_ids(edge_string)
+                    .ok_or(ParseGraphError(format!(
+                        "edge {edge_string} was not specified in rule table"
+                    )))?;
 
-            for &(u, v) in edges.iter() {
+            for (i, &(u, v)) in edges.iter().enumerate() {
                 let pos_u = cursor[u];
                 vals[pos_u] = (v, edge_num);
                 cursor[u] += 1;
@@ -65,9 +148,20 @@ impl Graph {
                 let pos_v = cursor[v];
                 vals[pos_v] = (u, edge_rev_num);
                 cursor[v] += 1;
+
+                if let Some(edge_weights) = edge_weights {
+                    weights[pos_u] = edge_weights[i];
+                    weights[pos_v] = edge_weights[i];
+                }
             }
         }
-        Ok(Graph { n, seps, vals })
+        Ok(Graph {
+            n,
+            seps,
+            vals,
+            weights,
+            node_weights: Vec::new(),
+        })
     }
 
     pub(crate) fn num_vertices(&self) -> usize {
@@ -79,11 +173,21 @@ impl Graph {
         let end = self.seps[u + 1];
         &self.vals[start..end]
     }
+
+    // weights of the edges returned by neighbors, zero if the graph is unweighted
+    pub(crate) fn neighbor_weights(&self, u: usize) -> impl Iterator<Item = f64> + '_ {
+        let (start, end) = (self.seps[u], self.seps[u + 1]);
+        (start..end).map(|i| self.weights.get(i).copied().unwrap_or(0.0))
+    }
+
+    pub(crate) fn node_weight(&self, u: usize) -> f64 {
+        self.node_weights.get(u).copied().unwrap_or(0.0)
+    }
 }
 
 /// Error type for reporting invalid graph definitions.
 #[derive(Debug)]
-pub struct ParseGraphError(String);
+pub struct ParseGraphError(pub(crate) String);
 
 impl fmt::Display for ParseGraphError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -101,6 +205,7 @@ impl Error for ParseGraphError {
 ///
 /// # Usage
 /// Can be constructed using [`Sets::new`] and then passed to `reach`.
+#[derive(Clone)]
 pub struct Sets(Vec<Vec<bool>>);
 
 impl Sets {
@@ -108,6 +213,8 @@ impl Sets {
     ///
     /// Each set label must be defined in the `Ruletable`.
     /// Duplicates within a set cause an error.
+    /// Sets declared in the `Ruletable` but missing from `sets` are empty, use
+    /// [`Sets::new_strict`] to reject them instead.
     pub fn new(
         sets: &HashMap<String, Vec<usize>>,
         ruletable: &Ruletable,
@@ -133,6 +240,30 @@ impl Sets {
         Ok(Sets(z))
     }
 
+    /// Constructs a new `Sets` like [`Sets::new`], but requires every set declared in the `Ruletable`.
+    ///
+    /// [`Sets::new`] treats declared sets missing from `sets` as empty, which
+    /// hides misspelled or forgotten sets. Returns an error listing the missing
+    /// sets in declaration order in this case, in addition to the errors of
+    /// `Sets::new`.
+    pub fn new_strict(
+        sets: &HashMap<String, Vec<usize>>,
+        ruletable: &Ruletable,
+    ) -> Result<Sets, ParseSetsError> {
+        let missing: Vec<_> = ruletable
+            .set_names()
+            .into_iter()
+            .filter(|name| !sets.contains_key(name))
+            .collect();
+        if !missing.is_empty() {
+            return Err(ParseSetsError(format!(
+                "sets declared in rule table were not specified: {}",
+                missing.join(", ")
+            )));
+        }
+        Self::new(sets, ruletable)
+    }
+
     pub(crate) fn contains(&self, set_id: usize, element: usize) -> bool {
         if element >= self.0[set_id].len() {
             return false;
@@ -140,6 +271,23 @@ impl Sets {
         self.0[set_id][element]
     }
 
+    pub(crate) fn insert(&mut self, set_id: usize, element: usize) {
+        if element >= self.0[set_id].len() {
+            self.0[set_id].resize(element + 1, false);
+        }
+        self.0[set_id][element] = true;
+    }
+
+    pub(crate) fn remove(&mut self, set_id: usize, element: usize) {
+        if element < self.0[set_id].len() {
+            self.0[set_id][element] = false;
+        }
+    }
+
+    pub(crate) fn clear(&mut self, set_id: usize) {
+        self.0[set_id].clear();
+    }
+
     pub(crate) fn elements(&self, set_id: usize) -> impl Iterator<Item = usize> + '_ {
         self.0[set_id]
             .iter()
@@ -155,7 +303,7 @@ impl Sets {
 
 /// Error type for reporting invalid set definitions.
 #[derive(Debug)]
-pub struct ParseSetsError(String);
+pub struct ParseSetsError(pub(crate) String);
 
 impl fmt::Display for ParseSetsError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -4,11 +4,19 @@
 //! Best used through the wrapper packages ciflypy and ciflyr for Python and R.
 //! Find more information on the [CIfly website](https://cifly.dev).
 
+pub mod algorithms;
 mod array_nd;
-mod expression;
+pub mod bitset;
+pub mod equivalence;
+pub mod estimand;
+pub mod expression;
 pub mod instance;
+pub mod minimize;
+pub mod paths;
 pub mod reach;
 pub mod ruletable;
+pub mod state_graph;
+pub mod tables;
 pub use instance::{Graph, ParseGraphError, ParseSetsError, Sets};
 pub use reach::Settings;
 pub use ruletable::{ReadRuletableError, Ruletable};
@@ -0,0 +1,566 @@
+//! Minimizing rule tables.
+//!
+//! Hand-written rule tables often contain colors that are never reached, colors
+//! that behave the same, rules that never apply because earlier rules match all
+//! their cases and expressions that can be written more briefly. [`minimize`]
+//! removes all of these and returns a smaller rule table for which `reach`
+//! returns the same nodes on every graph, together with a report of the changes.
+//!
+//! Equivalent colors are found by partition refinement on the colored transition
+//! system, whose states are pairs of an edge type and a color. Two colors are
+//! merged if, for every edge type, their states are output states alike and
+//! allow transitions to the same classes of colors for the same sets containing
+//! `current` and `next`. Expressions are compared by their truth tables, so the
+//! time needed is exponential in the number of sets referred to in the rules.
+
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::{
+    expression::{Expression, Op, RuletableAtom, MAX_VALUATION_SETS},
+    instance::Sets,
+    ruletable::Ruletable,
+};
+
+/// Changes made by [`minimize`].
+///
+/// Rules are referred to by their position in the input rule table, starting
+/// from zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MinimizationReport {
+    /// The colors of no state reachable from a start state, which were removed.
+    pub unreachable_colors: Vec<String>,
+    /// The classes of equivalent colors with more than one color, each merged into its first color.
+    pub merged_colors: Vec<Vec<String>>,
+    /// The rules that were removed because they never apply.
+    pub removed_rules: Vec<usize>,
+    /// The rules whose expression was simplified.
+    pub simplified_rules: Vec<usize>,
+    /// The number of rules added in front of the rules to retain the transitions
+    /// between merged colors that the rules no longer distinguish.
+    pub added_rules: usize,
+}
+
+/// Minimized rule table, as returned by [`minimize`].
+pub struct Minimized {
+    pub ruletable: Ruletable,
+    /// The source of the minimized rule table, with definitions expanded.
+    pub source: String,
+    pub report: MinimizationReport,
+}
+
+/// Returns a minimized rule table for which `reach` returns the same nodes as for `ruletable`.
+///
+/// The minimized rule table declares the same edge types and sets, in the same
+/// order, such that graphs and sets can be used with both rule tables. It
+/// contains
+/// - only the colors of states reachable from a start state, where a transition
+///   is possible if its expression is satisfiable,
+/// - one color for every class of equivalent colors, named by the first color of
+///   the class in declaration order,
+/// - only rules that are the first matching rule for some transition, and
+/// - simplified expressions, with constant expressions replaced by `true` or
+///   `false` and operands of `and` and `or` removed if they do not change the
+///   result.
+///
+/// # Errors
+/// Returns an error if the rules refer to more than 10 sets, as truth tables over
+/// all combinations of sets containing `current` and `next` are computed, or if
+/// the minimized rule table cannot be parsed.
+pub fn minimize(ruletable: &Ruletable) -> Result<Minimized, MinimizeError> {
+    let valuations = Valuations::new(ruletable)?;
+    let (num_edges, num_colors) = (ruletable.num_edges(), ruletable.num_colors());
+    let color_names = ruletable.color_names();
+    let has_colors = !color_names.is_empty();
+    let case = |e1: usize, c1: usize, e2: usize, c2: usize| {
+        ((e1 * num_colors + c1) * num_edges + e2) * num_colors + c2
+    };
+    let mut guards = vec![Vec::new(); num_edges * num_colors * num_edges * num_colors];
+    for e1 in 0..num_edges {
+        for c1 in 0..num_colors {
+            for e2 in 0..num_edges {
+                for c2 in 0..num_colors {
+                    guards[case(e1, c1, e2, c2)] =
+                        valuations.truth_table(ruletable.rule_expression(e1, c1, e2, c2));
+                }
+            }
+        }
+    }
+
+    let reachable = reachable_colors(ruletable, |e1, c1, e2, c2| {
+        guards[case(e1, c1, e2, c2)].contains(&true)
+    });
+    let live: Vec<_> = (0..num_colors).filter(|&c| reachable[c]).collect();
+
+    // partition refinement, classes are numbered in order of their first color
+    let is_output = |e: usize, c: usize| ruletable.outputs().contains(&(e, c));
+    let mut class = vec![0; num_colors];
+    let mut num_classes = 0;
+    loop {
+        let mut signatures = HashMap::new();
+        let mut refined = vec![0; num_colors];
+        for &c in live.iter() {
+            let outputs: Vec<_> = (0..num_edges).map(|e| is_output(e, c)).collect();
+            let mut transitions = Vec::new();
+            for e1 in 0..num_edges {
+                for e2 in 0..num_edges {
+                    for k in 0..num_classes {
+                        transitions.push(or_truth_tables(
+                            live.iter()
+                                .filter(|&&c2| class[c2] == k)
+                                .map(|&c2| &guards[case(e1, c, e2, c2)]),
+                            valuations.len(),
+                        ));
+                    }
+                }
+            }
+            let next_id = signatures.len();
+            refined[c] = *signatures
+                .entry((class[c], outputs, transitions))
+                .or_insert(next_id);
+        }
+        let stable = signatures.len() == num_classes;
+        num_classes = signatures.len();
+        class = refined;
+        if stable {
+            break;
+        }
+    }
+    let members: Vec<Vec<_>> = (0..num_classes)
+        .map(|k| live.iter().copied().filter(|&c| class[c] == k).collect())
+        .collect();
+    let new_color = |c: usize| reachable[c].then_some(class[c]);
+
+    let mut report = MinimizationReport::default();
+    if has_colors {
+        report.unreachable_colors = (0..num_colors)
+            .filter(|&c| !reachable[c])
+            .map(|c| color_names[c].clone())
+            .collect();
+        report.merged_colors = members
+            .iter()
+            .filter(|m| m.len() > 1)
+            .map(|m| m.iter().map(|&c| color_names[c].clone()).collect())
+            .collect();
+    }
+
+    // rules of the input with colors replaced by their class
+    let set_names = ruletable.set_names();
+    let mut rules = Vec::new();
+    for (i, spec) in ruletable.rule_specs().into_iter().enumerate() {
+        let edges = |names: &[String]| -> Vec<usize> {
+            names
+                .iter()
+                .map(|e| ruletable.get_edge_ids(e).expect("edge should exist").0)
+                .collect()
+        };
+        let colors = |names: &[String]| -> Vec<usize> {
+            if !has_colors {
+                return vec![0];
+            }
+            let mut colors: Vec<_> = names
+                .iter()
+                .filter_map(|c| new_color(ruletable.get_color_id(c).expect("color should exist")))
+                .collect();
+            colors.sort_unstable();
+            colors.dedup();
+            colors
+        };
+        let rule = MinimizedRule {
+            origin: Some(i),
+            prev_edges: edges(&spec.prev_edges),
+            prev_colors: colors(&spec.prev_colors),
+            next_edges: edges(&spec.next_edges),
+            next_colors: colors(&spec.next_colors),
+            expression: simplify(&spec.expression, &valuations),
+        };
+        if rule.prev_colors.is_empty() || rule.next_colors.is_empty() {
+            report.removed_rules.push(i);
+            continue;
+        }
+        if rule.expression.to_ruletable_string(&set_names)
+            != spec.expression.to_ruletable_string(&set_names)
+        {
+            report.simplified_rules.push(i);
+        }
+        rules.push(rule);
+    }
+
+    // the transitions between classes are those of the first color of the class, rules
+    // matching them with a different truth table are preceded by a rule for the case
+    let mut added = Vec::new();
+    for e1 in 0..num_edges {
+        for k1 in 0..num_classes {
+            for e2 in 0..num_edges {
+                for k2 in 0..num_classes {
+                    let c1 = members[k1][0];
+                    let target = or_truth_tables(
+                        members[k2].iter().map(|&c2| &guards[case(e1, c1, e2, c2)]),
+                        valuations.len(),
+                    );
+                    let found = match rules.iter().find(|r| r.matches(e1, k1, e2, k2)) {
+                        Some(rule) => valuations.truth_table(Some(&rule.expression)),
+                        None => vec![false; valuations.len()],
+                    };
+                    if found == target {
+                        continue;
+                    }
+                    let mut operands = Vec::new();
+                    for &c2 in members[k2].iter() {
+                        if let Some(e) = ruletable.rule_expression(e1, c1, e2, c2) {
+                            if !operands.contains(e) {
+                                operands.push(e.clone());
+                            }
+                        }
+                    }
+                    let expression = match operands.len() {
+                        0 => Expression::Atom(RuletableAtom::False),
+                        1 => operands.pop().unwrap(),
+                        _ => Expression::Junction(Op::Or, operands),
+                    };
+                    added.push(MinimizedRule {
+                        origin: None,
+                        prev_edges: vec![e1],
+                        prev_colors: vec![k1],
+                        next_edges: vec![e2],
+                        next_colors: vec![k2],
+                        expression: simplify(&expression, &valuations),
+                    });
+                }
+            }
+        }
+    }
+    report.added_rules = added.len();
+    added.append(&mut rules);
+    let mut rules = added;
+
+    // rules that are not the first matching rule of any case never apply
+    let mut applies = vec![false; rules.len()];
+    for e1 in 0..num_edges {
+        for k1 in 0..num_classes {
+            for e2 in 0..num_edges {
+                for k2 in 0..num_classes {
+                    if let Some(i) = rules.iter().position(|r| r.matches(e1, k1, e2, k2)) {
+                        applies[i] = true;
+                    }
+                }
+            }
+        }
+    }
+    let mut applies = applies.into_iter();
+    rules.retain(|rule| {
+        let keep = applies.next().unwrap();
+        if !keep {
+            if let Some(i) = rule.origin {
+                report.removed_rules.push(i);
+                report.simplified_rules.retain(|&j| j != i);
+            }
+        }
+        keep
+    });
+    report.removed_rules.sort_unstable();
+
+    let class_names: Vec<_> = if has_colors {
+        members.iter().map(|m| color_names[m[0]].clone()).collect()
+    } else {
+        Vec::new()
+    };
+    let source = render(ruletable, &class_names, &new_color, &rules);
+    let minimized = Ruletable::from_multiline_string(&source)
+        .map_err(|e| MinimizeError(format!("minimized rule table could not be parsed: {e}")))?;
+    Ok(Minimized {
+        ruletable: minimized,
+        source,
+        report,
+    })
+}
+
+/// Error type for reporting rule tables that cannot be minimized.
+#[derive(Debug)]
+pub struct MinimizeError(String);
+
+impl fmt::Display for MinimizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Error when minimizing rule table: {}", self.0)
+    }
+}
+
+impl Error for MinimizeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+// Rule of the minimized rule table, colors are the ids of the classes of colors.
+struct MinimizedRule {
+    origin: Option<usize>,
+    prev_edges: Vec<usize>,
+    prev_colors: Vec<usize>,
+    next_edges: Vec<usize>,
+    next_colors: Vec<usize>,
+    expression: Expression,
+}
+
+impl MinimizedRule {
+    fn matches(&self, e1: usize, c1: usize, e2: usize, c2: usize) -> bool {
+        self.prev_edges.contains(&e1)
+            && self.prev_colors.contains(&c1)
+            && self.next_edges.contains(&e2)
+            && self.next_colors.contains(&c2)
+    }
+}
+
+// Truth tables of expressions over the sets referred to in the rules of a rule
+// table. Bit 2i of a valuation is whether current is in the i-th of these sets
+// and bit 2i + 1 whether next is.
+struct Valuations<'a> {
+    ruletable: &'a Ruletable,
+    used: Vec<usize>,
+}
+
+impl<'a> Valuations<'a> {
+    fn new(ruletable: &'a Ruletable) -> Result<Self, MinimizeError> {
+        let used: Vec<_> = (0..ruletable.num_sets())
+            .filter(|&s| ruletable.uses_set(s))
+            .collect();
+        if used.len() > MAX_VALUATION_SETS {
+            return Err(MinimizeError(format!(
+                "rules refer to {} sets, at most {MAX_VALUATION_SETS} are supported",
+                used.len()
+            )));
+        }
+        Ok(Valuations { ruletable, used })
+    }
+
+    fn len(&self) -> usize {
+        1 << (2 * self.used.len())
+    }
+
+    // a missing expression is false for all valuations
+    fn truth_table(&self, expression: Option<&Expression>) -> Vec<bool> {
+        let Some(expression) = expression else {
+            return vec![false; self.len()];
+        };
+        let mut sets =
+            Sets::new(&HashMap::new(), self.ruletable).expect("empty sets should be valid");
+        // node 0 stands for current and node 1 for next
+        (0..self.len())
+            .map(|mask| {
+                for (i, &s) in self.used.iter().enumerate() {
+                    for node in 0..2 {
+                        if mask >> (2 * i + node) & 1 == 1 {
+                            sets.insert(s, node);
+                        } else {
+                            sets.remove(s, node);
+                        }
+                    }
+                }
+                expression.evaluate(&sets, 0, 1)
+            })
+            .collect()
+    }
+}
+
+fn or_truth_tables<'a>(tables: impl Iterator<Item = &'a Vec<bool>>, len: usize) -> Vec<bool> {
+    let mut result = vec![false; len];
+    for table in tables {
+        for (r, &t) in result.iter_mut().zip(table.iter()) {
+            *r |= t;
+        }
+    }
+    result
+}
+
+// colors of the states reachable from a start state, where a state is a pair of
+// an edge type and a color and a transition is possible if it is satisfiable
+fn reachable_colors(
+    ruletable: &Ruletable,
+    is_satisfiable: impl Fn(usize, usize, usize, usize) -> bool,
+) -> Vec<bool> {
+    let (num_edges, num_colors) = (ruletable.num_edges(), ruletable.num_colors());
+    let state = |e: usize, c: usize| e * num_colors + c;
+    let mut visited = vec![false; num_edges * num_colors];
+    let mut stack = Vec::new();
+    for &(_, e, c) in ruletable.starts().iter() {
+        if !visited[state(e, c)] {
+            visited[state(e, c)] = true;
+            stack.push((e, c));
+        }
+    }
+    while let Some((e1, c1)) = stack.pop() {
+        for e2 in 0..num_edges {
+            for c2 in 0..num_colors {
+                if !visited[state(e2, c2)] && is_satisfiable(e1, c1, e2, c2) {
+                    visited[state(e2, c2)] = true;
+                    stack.push((e2, c2));
+                }
+            }
+        }
+    }
+    let mut reachable: Vec<_> = (0..num_colors)
+        .map(|c| (0..num_edges).any(|e| visited[state(e, c)]))
+        .collect();
+    // the minimized rule table needs a color if the input has colors
+    if !reachable.contains(&true) {
+        reachable[0] = true;
+    }
+    reachable
+}
+
+fn simplify(expression: &Expression, valuations: &Valuations) -> Expression {
+    let simplified = match expression {
+        Expression::Junction(Op::Not, es) => match simplify(&es[0], valuations) {
+            Expression::Atom(RuletableAtom::True) => Expression::Atom(RuletableAtom::False),
+            Expression::Atom(RuletableAtom::False) => Expression::Atom(RuletableAtom::True),
+            Expression::Junction(Op::Not, mut es) => es.pop().unwrap(),
+            Expression::Junction(Op::In, es) => Expression::Junction(Op::NotIn, es),
+            Expression::Junction(Op::NotIn, es) => Expression::Junction(Op::In, es),
+            e => Expression::Junction(Op::Not, vec![e]),
+        },
+        Expression::Junction(op @ (Op::And | Op::Or), es) => {
+            let (unit, zero) = if *op == Op::And {
+                (RuletableAtom::True, RuletableAtom::False)
+            } else {
+                (RuletableAtom::False, RuletableAtom::True)
+            };
+            let mut operands = Vec::new();
+            for e in es.iter() {
+                let flattened = match simplify(e, valuations) {
+                    Expression::Junction(inner, inner_es) if inner == *op => inner_es,
+                    e => vec![e],
+                };
+                for e in flattened {
+                    if e == Expression::Atom(zero) {
+                        return Expression::Atom(zero);
+                    }
+                    if e != Expression::Atom(unit) && !operands.contains(&e) {
+                        operands.push(e);
+                    }
+                }
+            }
+            // drop operands that do not change the truth table
+            let table = valuations.truth_table(Some(&junction(*op, operands.clone(), unit)));
+            let mut i = 0;
+            while i < operands.len() && operands.len() > 1 {
+                let mut without = operands.clone();
+                without.remove(i);
+                if valuations.truth_table(Some(&junction(*op, without.clone(), unit))) == table {
+                    operands = without;
+                } else {
+                    i += 1;
+                }
+            }
+            junction(*op, operands, unit)
+        }
+        e => e.clone(),
+    };
+    let table = valuations.truth_table(Some(&simplified));
+    if table.iter().all(|&t| t) {
+        Expression::Atom(RuletableAtom::True)
+    } else if table.iter().all(|&t| !t) {
+        Expression::Atom(RuletableAtom::False)
+    } else {
+        simplified
+    }
+}
+
+fn junction(op: Op, mut operands: Vec<Expression>, unit: RuletableAtom) -> Expression {
+    match operands.len() {
+        0 => Expression::Atom(unit),
+        1 => operands.pop().unwrap(),
+        _ => Expression::Junction(op, operands),
+    }
+}
+
+fn render(
+    ruletable: &Ruletable,
+    class_names: &[String],
+    new_color: &impl Fn(usize) -> Option<usize>,
+    rules: &[MinimizedRule],
+) -> String {
+    let edge_names = ruletable.edge_names();
+    let mut declared = Vec::new();
+    for (i, edge) in edge_names.iter().enumerate() {
+        let reverse = ruletable
+            .reverse_edge_name(edge)
+            .expect("edge should exist");
+        if reverse == *edge {
+            declared.push(edge.clone());
+        } else if !edge_names[..i].contains(&reverse) {
+            declared.push(format!("{edge} {reverse}"));
+        }
+    }
+    let pattern = |edges: &[usize], colors: &[usize]| {
+        let mut s = if edges.len() == edge_names.len() {
+            "...".to_owned()
+        } else {
+            edges
+                .iter()
+                .map(|&e| edge_names[e].as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if !class_names.is_empty() {
+            if colors.len() == class_names.len() {
+                s.push_str(" [...]");
+            } else {
+                let colors: Vec<_> = colors.iter().map(|&c| class_names[c].as_str()).collect();
+                s.push_str(&format!(" [{}]", colors.join(", ")));
+            }
+        }
+        s
+    };
+
+    let set_names = ruletable.set_names();
+    let mut source = format!("EDGES {}\n", declared.join(", "));
+    if !set_names.is_empty() {
+        source.push_str(&format!("SETS {}\n", set_names.join(", ")));
+    }
+    if !class_names.is_empty() {
+        source.push_str(&format!("COLORS {}\n", class_names.join(", ")));
+    }
+
+    // start states grouped by set and color, output states grouped by color
+    let mut starts: Vec<(usize, usize, Vec<usize>)> = Vec::new();
+    for &(s, e, c) in ruletable.starts().iter() {
+        let c = new_color(c).expect("start colors should be reachable");
+        match starts.iter_mut().find(|(s2, c2, _)| (*s2, *c2) == (s, c)) {
+            Some((_, _, edges)) if !edges.contains(&e) => edges.push(e),
+            Some(_) => (),
+            None => starts.push((s, c, vec![e])),
+        }
+    }
+    for (s, c, mut edges) in starts {
+        edges.sort_unstable();
+        source.push_str(&format!(
+            "START {} AT {}\n",
+            pattern(&edges, &[c]),
+            set_names[s]
+        ));
+    }
+    let mut outputs: Vec<(usize, Vec<usize>)> = Vec::new();
+    for &(e, c) in ruletable.outputs().iter() {
+        let Some(c) = new_color(c) else {
+            continue;
+        };
+        match outputs.iter_mut().find(|(c2, _)| *c2 == c) {
+            Some((_, edges)) if !edges.contains(&e) => edges.push(e),
+            Some(_) => (),
+            None => outputs.push((c, vec![e])),
+        }
+    }
+    for (c, mut edges) in outputs {
+        edges.sort_unstable();
+        source.push_str(&format!("OUTPUT {}\n", pattern(&edges, &[c])));
+    }
+
+    source.push('\n');
+    for rule in rules.iter() {
+        source.push_str(&format!(
+            "{} | {} | {}\n",
+            pattern(&rule.prev_edges, &rule.prev_colors),
+            pattern(&rule.next_edges, &rule.next_colors),
+            rule.expression.to_ruletable_string(&set_names)
+        ));
+    }
+    source
+}
@@ -0,0 +1,214 @@
+//! Counting walks and enumerating simple paths allowed by a rule table.
+//!
+//! [`reach`](crate::reach::reach) only decides which nodes can be reached, which
+//! is what makes it run in linear time. The functions in this module explain how
+//! they are reached, for example by listing the paths between two sets of nodes
+//! that are allowed by a rule table. Both work on the state graph, whose states
+//! are triples of a node, the edge type the node was reached by and a color, and
+//! whose transitions are the ones allowed by the rule table.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::{
+    instance::{Graph, Sets},
+    reach::{is_output, start_states, transitions, State},
+    ruletable::Ruletable,
+};
+
+/// Counts the walks in the state graph from a start state to an output state.
+///
+/// Returns the reachable nodes in ascending order, each paired with the number of
+/// walks in the state graph that start in a start state and end in an output
+/// state at the node. Walks that differ only in their colors are counted
+/// separately. Counts larger than `u128::MAX` are capped at `u128::MAX`.
+///
+/// Returns `None` if a cycle in the state graph can be reached from the start
+/// states, as there are infinitely many walks in this case. This is usually the
+/// case for tables that allow walks to turn around, such as those for
+/// d-connection, for which [`simple_paths`] can be used instead.
+pub fn count_walks(
+    graph: &Graph,
+    sets: &Sets,
+    ruletable: &Ruletable,
+) -> Option<Vec<(usize, u128)>> {
+    let starts = start_states(sets, ruletable);
+    let order = topological_order(graph, sets, ruletable, &starts)?;
+
+    let mut counts: HashMap<State, u128> = starts.iter().map(|&s| (s, 1)).collect();
+    let mut per_node = BTreeMap::new();
+    for s1 in order {
+        let count = counts[&s1];
+        if is_output(ruletable, s1) {
+            let total = per_node.entry(s1.node).or_insert(0_u128);
+            *total = total.saturating_add(count);
+        }
+        for s2 in transitions(graph, sets, ruletable, s1) {
+            let next = counts.entry(s2).or_insert(0);
+            *next = next.saturating_add(count);
+        }
+    }
+    Some(per_node.into_iter().collect())
+}
+
+// reachable states ordered such that all transitions go forward, or None if
+// the reachable part of the state graph contains a cycle
+fn topological_order(
+    graph: &Graph,
+    sets: &Sets,
+    ruletable: &Ruletable,
+    starts: &[State],
+) -> Option<Vec<State>> {
+    // states are on the stack while they are in progress and finished afterwards
+    let mut finished = HashMap::new();
+    let mut order = Vec::new();
+    for &start in starts.iter() {
+        if finished.contains_key(&start) {
+            continue;
+        }
+        finished.insert(start, false);
+        let mut stack = vec![(start, transitions(graph, sets, ruletable, start))];
+        while let Some((s1, successors)) = stack.last_mut() {
+            let s1 = *s1;
+            match successors.next() {
+                Some(s2) => match finished.get(&s2) {
+                    Some(false) => return None,
+                    Some(true) => {}
+                    None => {
+                        finished.insert(s2, false);
+                        stack.push((s2, transitions(graph, sets, ruletable, s2)));
+                    }
+                },
+                None => {
+                    finished.insert(s1, true);
+                    order.push(s1);
+                    stack.pop();
+                }
+            }
+        }
+    }
+    order.reverse();
+    Some(order)
+}
+
+/// Options restricting the paths returned by [`simple_paths`].
+#[derive(Debug, Default, Clone)]
+pub struct PathOptions {
+    /// Stops the enumeration after this many paths if set.
+    pub max_paths: Option<usize>,
+    /// Only returns paths with at most this many edges if set.
+    pub max_length: Option<usize>,
+}
+
+/// A simple path returned by [`simple_paths`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    /// The nodes of the path, beginning with the start node.
+    pub nodes: Vec<usize>,
+    /// The edge types of the path as declared in the rule table, where `edges[i]`
+    /// is the edge between `nodes[i]` and `nodes[i + 1]`.
+    pub edges: Vec<String>,
+}
+
+/// Enumerates the simple paths allowed by the rule table that end in one of the `targets`.
+///
+/// A simple path is a walk in the graph without repeated nodes. It is returned as
+/// the list of its nodes and edges if it corresponds to a walk in the state graph
+/// from a start state to an output state at a target, that is, if the transitions
+/// along the path are allowed by the rule table. Every path is returned once, even
+/// if it corresponds to several walks in the state graph, while paths through the
+/// same nodes but along different edges, such as `-->` and `<->` between the same
+/// two nodes, are returned separately. Paths consisting of a single start node are
+/// included if the node is a target.
+///
+/// Only the transitions of the rule table are enforced, which may differ between
+/// walks and paths. For instance, `dconnected_admg` only opens colliders in `Z`,
+/// which is enough for walks but misses paths whose colliders are ancestors of
+/// `Z` without being in it. With the table `dconnected_paths_admg`, the ancestors
+/// of `Z` as set `A` and the outcomes as targets, the paths are all open paths.
+///
+/// Paths are returned in lexicographic order of their steps, where a step is
+/// compared by its node and then by the position of its edge in the rule table, so
+/// if the enumeration is stopped early by `options.max_paths`, the smallest paths
+/// in this order are returned. As there can be exponentially many simple paths, the
+/// running time is only bounded in terms of the number of returned paths if
+/// `options.max_paths` or `options.max_length` is set.
+pub fn simple_paths(
+    graph: &Graph,
+    sets: &Sets,
+    ruletable: &Ruletable,
+    targets: &[usize],
+    options: &PathOptions,
+) -> Vec<Path> {
+    let targets: HashSet<_> = targets.iter().copied().collect();
+    let max_paths = options.max_paths.unwrap_or(usize::MAX);
+    let max_length = options.max_length.unwrap_or(usize::MAX);
+    let edge_strings = ruletable.get_edge_strings();
+
+    let is_valid_end = |node: usize, states: &[State]| {
+        targets.contains(&node) && states.iter().any(|&s| is_output(ruletable, s))
+    };
+    let to_path = |nodes: &[usize], edges: &[usize]| Path {
+        nodes: nodes.to_vec(),
+        edges: edges.iter().map(|&e| edge_strings[e].clone()).collect(),
+    };
+    // the next nodes and edges of the path in ascending order with their states
+    let children = |states: &[State], path: &[usize]| {
+        let mut children: BTreeMap<(usize, usize), Vec<State>> = BTreeMap::new();
+        for &s1 in states.iter() {
+            for s2 in transitions(graph, sets, ruletable, s1) {
+                if !path.contains(&s2.node) {
+                    let states = children.entry((s2.node, s2.edge)).or_default();
+                    if !states.contains(&s2) {
+                        states.push(s2);
+                    }
+                }
+            }
+        }
+        children.into_iter()
+    };
+
+    let mut starts: BTreeMap<usize, Vec<State>> = BTreeMap::new();
+    for s in start_states(sets, ruletable) {
+        starts.entry(s.node).or_default().push(s);
+    }
+
+    let mut paths = Vec::new();
+    for (node, states) in starts {
+        let mut path = vec![node];
+        let mut edges = Vec::new();
+        if paths.len() < max_paths && is_valid_end(node, &states) {
+            paths.push(to_path(&path, &edges));
+        }
+        let mut stack = vec![children(&states, &path)];
+        if max_length == 0 {
+            stack.clear();
+        }
+        while let Some(frame) = stack.last_mut() {
+            if paths.len() >= max_paths {
+                return paths;
+            }
+            match frame.next() {
+                Some(((v, edge), states)) => {
+                    path.push(v);
+                    edges.push(edge);
+                    if is_valid_end(v, &states) {
+                        paths.push(to_path(&path, &edges));
+                    }
+                    if path.len() - 1 < max_length {
+                        let next = children(&states, &path);
+                        stack.push(next);
+                    } else {
+                        path.pop();
+                        edges.pop();
+                    }
+                }
+                None => {
+                    stack.pop();
+                    path.pop();
+                    edges.pop();
+                }
+            }
+        }
+    }
+    paths
+}
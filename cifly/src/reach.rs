@@ -1,8 +1,12 @@
-use std::{cmp, collections::VecDeque};
+use std::{
+    cmp::{self, Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+};
 
 use crate::{
     array_nd::{Array2D, Array3D},
-    instance::{Graph, Sets},
+    bitset::{BitMatrix, BitSet},
+    instance::{Graph, ParseSetsError, Sets},
     ruletable::Ruletable,
 };
 
@@ -13,6 +17,8 @@ pub struct Settings {
     pub verbose: bool,
     /// Formats output using one-indexed nodes if true.
     pub fmt_one_indexed: bool,
+    /// Restricts the search to walks with at most this many edges if set.
+    pub max_depth: Option<usize>,
 }
 
 impl Settings {
@@ -21,6 +27,7 @@ impl Settings {
         Self {
             verbose,
             fmt_one_indexed,
+            max_depth: None,
         }
     }
 }
@@ -35,7 +42,218 @@ impl Settings {
 ///
 /// # Returns
 /// A `Vec<usize>` containing the node indices that are reachable and satisfy output constraints.
+/// The nodes are in the order in which the breadth-first search reaches them. This
+/// order is deterministic, as the construction of the graph is, see [`Graph::new`].
+/// Use [`reach_sorted`] or [`reach_bitset`] for results that are independent of
+/// the search order.
 pub fn reach(graph: &Graph, sets: &Sets, ruletable: &Ruletable, settings: &Settings) -> Vec<usize> {
+    let mut res = Vec::new();
+    let mut added = vec![false; cmp::max(graph.num_vertices(), sets.max_size())];
+    search(graph, sets, ruletable, settings, |s, _, _, is_output| {
+        if is_output && !added[s.node] {
+            res.push(s.node);
+            added[s.node] = true;
+        }
+        false
+    });
+    res
+}
+
+/// Computes the same nodes as [`reach`] in ascending order.
+pub fn reach_sorted(
+    graph: &Graph,
+    sets: &Sets,
+    ruletable: &Ruletable,
+    settings: &Settings,
+) -> Vec<usize> {
+    reach_bitset(graph, sets, ruletable, settings)
+        .iter()
+        .collect()
+}
+
+/// Computes the same nodes as [`reach`] as a bitset.
+///
+/// The set holds the nodes up to the largest node in `graph` or `sets`, which
+/// includes all nodes that can be reached.
+pub fn reach_bitset(
+    graph: &Graph,
+    sets: &Sets,
+    ruletable: &Ruletable,
+    settings: &Settings,
+) -> BitSet {
+    let mut res = BitSet::new(cmp::max(graph.num_vertices(), sets.max_size()));
+    search(graph, sets, ruletable, settings, |s, _, _, is_output| {
+        if is_output {
+            res.insert(s.node);
+        }
+        false
+    });
+    res
+}
+
+/// Computes the reachable nodes together with the length of their shortest walk.
+///
+/// Runs the same search as [`reach`] and returns the same nodes in the same order,
+/// each paired with the smallest number of edges of a walk in the state graph that
+/// reaches it in an output state. Nodes in start states have distance zero.
+pub fn reach_distances(
+    graph: &Graph,
+    sets: &Sets,
+    ruletable: &Ruletable,
+    settings: &Settings,
+) -> Vec<(usize, usize)> {
+    let mut res = Vec::new();
+    let mut added = vec![false; cmp::max(graph.num_vertices(), sets.max_size())];
+    search(
+        graph,
+        sets,
+        ruletable,
+        settings,
+        |s, _, depth, is_output| {
+            if is_output && !added[s.node] {
+                res.push((s.node, depth));
+                added[s.node] = true;
+            }
+            false
+        },
+    );
+    res
+}
+
+/// Searches for a walk to one of the `targets` that ends in an output state.
+///
+/// Runs the same search as [`reach`] but stops as soon as a target node is
+/// reached in an output state, which avoids exploring the full state space if
+/// only membership of the targets is of interest, as for d-connection tests.
+///
+/// # Returns
+/// The nodes of a walk from a start node to the first target reached, ending
+/// with the target, or `None` if no target is reachable.
+pub fn reach_any(
+    graph: &Graph,
+    sets: &Sets,
+    ruletable: &Ruletable,
+    targets: &[usize],
+) -> Option<Vec<usize>> {
+    let mut is_target = BitSet::new(targets.iter().max().map_or(0, |&t| t + 1));
+    for &t in targets.iter() {
+        is_target.insert(t);
+    }
+    let mut parents = HashMap::new();
+    let mut found = None;
+    search(
+        graph,
+        sets,
+        ruletable,
+        &Settings::default(),
+        |s, parent, _, is_output| {
+            if let Some(parent) = parent {
+                parents.insert(s, parent);
+            }
+            if is_output && is_target.contains(s.node) {
+                found = Some(s);
+            }
+            found.is_some()
+        },
+    );
+
+    let mut walk: Vec<_> = std::iter::successors(found, |s| parents.get(s).copied())
+        .map(|s| s.node)
+        .collect();
+    walk.reverse();
+    found.map(|_| walk)
+}
+
+/// Checks whether `node` is reachable, stopping as soon as it is reached.
+///
+/// Equivalent to `reach(...).contains(&node)` but usually much faster, see [`reach_any`].
+pub fn reaches(graph: &Graph, sets: &Sets, ruletable: &Ruletable, node: usize) -> bool {
+    reach_any(graph, sets, ruletable, &[node]).is_some()
+}
+
+/// Computes the reachable nodes together with the minimum cost of a walk reaching them.
+///
+/// The cost of a walk is the sum of the weights of its edges and of its nodes,
+/// counting repeated edges and nodes every time they occur. Weights are set with
+/// [`Graph::new_weighted`] and [`Graph::set_node_weights`], missing weights are
+/// zero. The walks are those of [`reach`], so the same nodes are returned, each
+/// paired with the smallest cost of a walk in the state graph that reaches it in an
+/// output state, in order of increasing cost.
+pub fn reach_weighted(graph: &Graph, sets: &Sets, ruletable: &Ruletable) -> Vec<(usize, f64)> {
+    let mut res = Vec::new();
+    let mut added = vec![false; cmp::max(graph.num_vertices(), sets.max_size())];
+    let mut costs: HashMap<State, f64> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    for s in start_states(sets, ruletable) {
+        let cost = graph.node_weight(s.node);
+        costs.insert(s, cost);
+        queue.push(Reverse((Cost(cost), s.node, s.edge, s.color)));
+    }
+
+    while let Some(Reverse((Cost(cost), node, edge, color))) = queue.pop() {
+        let s1 = State { node, edge, color };
+        if costs[&s1] < cost {
+            continue;
+        }
+        if is_output(ruletable, s1) && !added[node] {
+            res.push((node, cost));
+            added[node] = true;
+        }
+        if node >= graph.num_vertices() {
+            continue;
+        }
+        for (&(u2, t), weight) in graph
+            .neighbors(node)
+            .iter()
+            .zip(graph.neighbor_weights(node))
+        {
+            let cost2 = cost + weight + graph.node_weight(u2);
+            for &c2 in ruletable.possible_colors(edge, color, t).iter() {
+                let s2 = State {
+                    node: u2,
+                    edge: t,
+                    color: c2,
+                };
+                if costs.get(&s2).is_some_and(|&c| c <= cost2) || !ruletable.pass(sets, s1, s2) {
+                    continue;
+                }
+                costs.insert(s2, cost2);
+                queue.push(Reverse((Cost(cost2), u2, t, c2)));
+            }
+        }
+    }
+    res
+}
+
+// non-negative cost ordered totally, weights are checked when they are set
+#[derive(Clone, Copy, PartialEq)]
+struct Cost(f64);
+
+impl Eq for Cost {}
+
+impl PartialOrd for Cost {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cost {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+// Breadth-first search over the state space. Every reached state is passed to
+// visit when it is processed, together with the state it was first found from,
+// the length of the shortest walk reaching it and whether it is an output state.
+// The search stops once visit returns true.
+fn search(
+    graph: &Graph,
+    sets: &Sets,
+    ruletable: &Ruletable,
+    settings: &Settings,
+    mut visit: impl FnMut(State, Option<State>, usize, bool) -> bool,
+) {
     let n = cmp::max(graph.num_vertices(), sets.max_size());
     let mut visited = Array3D::new(n, ruletable.num_edges(), ruletable.num_colors(), false);
     let mut queue = VecDeque::new();
@@ -59,8 +277,6 @@ pub fn reach(graph: &Graph, sets: &Sets, ruletable: &Ruletable, settings: &Setti
     for &(e, c) in ruletable.outputs() {
         *is_output.get_mut(e, c) = true;
     }
-    let mut res = Vec::new();
-    let mut added = vec![false; n];
 
     for (set, e, c) in ruletable.starts().iter().copied() {
         for v in sets.elements(set) {
@@ -76,21 +292,19 @@ pub fn reach(graph: &Graph, sets: &Sets, ruletable: &Ruletable, settings: &Setti
 
             // isolated nodes with high node id are handled separately
             if s.node >= graph.num_vertices() {
-                if !added[s.node] && *is_output.get(s.edge, s.color) {
-                    res.push(s.node);
-                    added[s.node] = true;
+                if visit(s, None, 0, *is_output.get(s.edge, s.color)) {
+                    return;
                 }
             } else {
-                queue.push_back(s);
+                queue.push_back((s, None, 0));
             };
         }
     }
 
     // perform BFS
-    while let Some(s1) = queue.pop_front() {
-        if !added[s1.node] && *is_output.get(s1.edge, s1.color) {
-            res.push(s1.node);
-            added[s1.node] = true;
+    while let Some((s1, parent, depth)) = queue.pop_front() {
+        if visit(s1, parent, depth, *is_output.get(s1.edge, s1.color)) {
+            return;
         }
         if settings.verbose {
             println!(
@@ -98,6 +312,12 @@ pub fn reach(graph: &Graph, sets: &Sets, ruletable: &Ruletable, settings: &Setti
                 s1.convert_to_string(ruletable, settings)
             );
         }
+        if settings
+            .max_depth
+            .is_some_and(|max_depth| depth >= max_depth)
+        {
+            continue;
+        }
         for &(u2, t) in graph.neighbors(s1.node).iter() {
             for &c2 in ruletable.possible_colors(s1.edge, s1.color, t).iter() {
                 let s2 = State {
@@ -107,7 +327,7 @@ pub fn reach(graph: &Graph, sets: &Sets, ruletable: &Ruletable, settings: &Setti
                 };
                 if !*visited.get(s2.node, s2.edge, s2.color) && ruletable.pass(sets, s1, s2) {
                     *visited.get_mut(s2.node, s2.edge, s2.color) = true;
-                    queue.push_back(s2);
+                    queue.push_back((s2, Some(s1), depth + 1));
                     if settings.verbose {
                         println!(
                             "  Found transition '{}', add state '{}' to queue",
@@ -119,7 +339,434 @@ pub fn reach(graph: &Graph, sets: &Sets, ruletable: &Ruletable, settings: &Setti
             }
         }
     }
-    res
+}
+
+/// Computes the reachable nodes for every node as the only element of the set `set_name`.
+///
+/// Row `v` of the returned matrix equals the output of [`reach`] if `set_name`
+/// contains only `v`, the other sets are taken from `sets`. This is much faster
+/// than calling `reach` once per node: if no rule refers to `set_name`, the state
+/// graph is the same for every node, so it is condensed into its strongly
+/// connected components once and the reachable nodes are propagated as bitsets
+/// along the condensation. Otherwise, `reach` is run for every node. The matrix
+/// has one row and column per node up to the largest node in `graph` or `sets`.
+///
+/// # Errors
+/// Returns an error if `set_name` is not declared in the rule table.
+pub fn reach_all_pairs(
+    graph: &Graph,
+    sets: &Sets,
+    ruletable: &Ruletable,
+    set_name: &str,
+) -> Result<BitMatrix, ParseSetsError> {
+    let set_id = ruletable
+        .get_set_id(set_name)
+        .ok_or(ParseSetsError(format!(
+            "set {set_name} was not specified in rule table"
+        )))?;
+    let n = cmp::max(graph.num_vertices(), sets.max_size());
+    let mut matrix = BitMatrix::new(n, n);
+
+    if ruletable.uses_set(set_id) {
+        let mut sets = sets.clone();
+        for v in 0..n {
+            sets.clear(set_id);
+            sets.insert(set_id, v);
+            for u in reach(graph, &sets, ruletable, &Settings::default()) {
+                matrix.insert(v, u);
+            }
+        }
+        return Ok(matrix);
+    }
+
+    let mut condensation = Condensation::new(graph, sets, ruletable, n);
+    let mut common = Vec::new();
+    for &(set, e, c) in ruletable
+        .starts()
+        .iter()
+        .filter(|&&(set, _, _)| set != set_id)
+    {
+        for v in sets.elements(set) {
+            common.push(condensation.component(State {
+                node: v,
+                edge: e,
+                color: c,
+            }));
+        }
+    }
+    for v in 0..n {
+        for &(_, e, c) in ruletable
+            .starts()
+            .iter()
+            .filter(|&&(set, _, _)| set == set_id)
+        {
+            let component = condensation.component(State {
+                node: v,
+                edge: e,
+                color: c,
+            });
+            matrix.union_row(v, &condensation.reachable, component);
+        }
+        for &component in common.iter() {
+            matrix.union_row(v, &condensation.reachable, component);
+        }
+    }
+    Ok(matrix)
+}
+
+/// Computes the reachable nodes separately for every node of a start state.
+///
+/// [`reach`] merges the walks from all start states, this function returns for
+/// every node `v` in a start state the nodes reached by walks that start in a
+/// start state at `v`, with the sets used by the rules unchanged. The sources
+/// are returned in ascending order, each with its reachable nodes in ascending
+/// order. Instead of one search per source, the sources are processed in blocks
+/// of 64, whose members are tracked as the bits of one word per state, so every
+/// block explores the state space once, possibly revisiting a state when it is
+/// reached from further sources.
+pub fn reach_by_source(
+    graph: &Graph,
+    sets: &Sets,
+    ruletable: &Ruletable,
+) -> Vec<(usize, Vec<usize>)> {
+    let n = cmp::max(graph.num_vertices(), sets.max_size());
+    let (num_edges, num_colors) = (ruletable.num_edges(), ruletable.num_colors());
+    let mut starts: Vec<_> = start_states(sets, ruletable);
+    starts.sort_by_key(|s| s.node);
+    let mut sources: Vec<_> = starts.iter().map(|s| s.node).collect();
+    sources.dedup();
+
+    let mut reached = BitMatrix::new(sources.len(), n);
+    for (block, block_sources) in sources.chunks(64).enumerate() {
+        let offset = 64 * block;
+        let mut masks = Array3D::new(n, num_edges, num_colors, 0_u64);
+        let mut queued = Array3D::new(n, num_edges, num_colors, false);
+        let mut queue = VecDeque::new();
+        for s in starts
+            .iter()
+            .filter(|s| block_sources.binary_search(&s.node).is_ok())
+        {
+            let bit = sources.binary_search(&s.node).unwrap() - offset;
+            *masks.get_mut(s.node, s.edge, s.color) |= 1 << bit;
+            if !*queued.get(s.node, s.edge, s.color) {
+                *queued.get_mut(s.node, s.edge, s.color) = true;
+                queue.push_back(*s);
+            }
+        }
+
+        while let Some(s1) = queue.pop_front() {
+            *queued.get_mut(s1.node, s1.edge, s1.color) = false;
+            let mask = *masks.get(s1.node, s1.edge, s1.color);
+            for s2 in transitions(graph, sets, ruletable, s1) {
+                let known = masks.get_mut(s2.node, s2.edge, s2.color);
+                if mask & !*known != 0 {
+                    *known |= mask;
+                    if !*queued.get(s2.node, s2.edge, s2.color) {
+                        *queued.get_mut(s2.node, s2.edge, s2.color) = true;
+                        queue.push_back(s2);
+                    }
+                }
+            }
+        }
+
+        for v in 0..n {
+            for &(e, c) in ruletable.outputs().iter() {
+                let mut mask = *masks.get(v, e, c);
+                while mask != 0 {
+                    reached.insert(offset + mask.trailing_zeros() as usize, v);
+                    mask &= mask - 1;
+                }
+            }
+        }
+    }
+
+    sources
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| (v, reached.row(i).collect()))
+        .collect()
+}
+
+/// Computes the nodes from which one of the `targets` is reachable as the only element of the set `set_name`.
+///
+/// Returns, in ascending order, every node `v` for which [`reach`] outputs a node
+/// in `targets` if `set_name` contains only `v`, the other sets are taken from
+/// `sets`. Instead of running `reach` once per candidate node, a single search
+/// starts from the output states at the targets and follows the transitions of
+/// the rule table backwards, which yields all start states from which a target is
+/// reachable. If a rule refers to `set_name`, the transitions depend on the start
+/// node, so `reach` is run for every node instead. The candidate nodes range up
+/// to the largest node in `graph` or `sets`.
+///
+/// # Errors
+/// Returns an error if `set_name` is not declared in the rule table.
+pub fn reach_reverse(
+    graph: &Graph,
+    sets: &Sets,
+    ruletable: &Ruletable,
+    set_name: &str,
+    targets: &[usize],
+) -> Result<Vec<usize>, ParseSetsError> {
+    let set_id = ruletable
+        .get_set_id(set_name)
+        .ok_or(ParseSetsError(format!(
+            "set {set_name} was not specified in rule table"
+        )))?;
+    let n = cmp::max(graph.num_vertices(), sets.max_size());
+
+    if ruletable.uses_set(set_id) {
+        let mut sets = sets.clone();
+        let mut sources = Vec::new();
+        for v in 0..n {
+            sets.clear(set_id);
+            sets.insert(set_id, v);
+            let reached = reach(graph, &sets, ruletable, &Settings::default());
+            if reached.iter().any(|u| targets.contains(u)) {
+                sources.push(v);
+            }
+        }
+        return Ok(sources);
+    }
+
+    let (num_edges, num_colors) = (ruletable.num_edges(), ruletable.num_colors());
+    let reverse_edges = ruletable.get_reverse_edge_ids();
+    // the edges and colors of the states that can transition into a state with
+    // the given edge and color
+    let mut predecessors = Array2D::new(num_edges, num_colors, Vec::new());
+    for e1 in 0..num_edges {
+        for c1 in 0..num_colors {
+            for e2 in 0..num_edges {
+                for &c2 in ruletable.possible_colors(e1, c1, e2).iter() {
+                    predecessors.get_mut(e2, c2).push((e1, c1));
+                }
+            }
+        }
+    }
+
+    let mut visited = Array3D::new(n, num_edges, num_colors, false);
+    let mut queue = VecDeque::new();
+    for &y in targets.iter().filter(|&&y| y < n) {
+        for &(e, c) in ruletable.outputs().iter() {
+            if !*visited.get(y, e, c) {
+                *visited.get_mut(y, e, c) = true;
+                queue.push_back(State {
+                    node: y,
+                    edge: e,
+                    color: c,
+                });
+            }
+        }
+    }
+    while let Some(s2) = queue.pop_front() {
+        if s2.node >= graph.num_vertices() {
+            continue;
+        }
+        for &(u1, t) in graph.neighbors(s2.node).iter() {
+            if reverse_edges[t] != s2.edge {
+                continue;
+            }
+            for &(e1, c1) in predecessors.get(s2.edge, s2.color).iter() {
+                let s1 = State {
+                    node: u1,
+                    edge: e1,
+                    color: c1,
+                };
+                if !*visited.get(u1, e1, c1) && ruletable.pass(sets, s1, s2) {
+                    *visited.get_mut(u1, e1, c1) = true;
+                    queue.push_back(s1);
+                }
+            }
+        }
+    }
+
+    let is_source = |v: usize, set: usize| {
+        ruletable
+            .starts()
+            .iter()
+            .any(|&(start_set, e, c)| start_set == set && *visited.get(v, e, c))
+    };
+    let reached_by_others = ruletable
+        .starts()
+        .iter()
+        .filter(|&&(set, _, _)| set != set_id)
+        .any(|&(set, _, _)| sets.elements(set).any(|v| is_source(v, set)));
+    Ok((0..n)
+        .filter(|&v| reached_by_others || is_source(v, set_id))
+        .collect())
+}
+
+// Strongly connected components of the state graph, found lazily with Tarjan's
+// algorithm. Components are numbered in the order they are completed, so every
+// component can only reach components with smaller numbers. Row k of reachable
+// holds the output nodes reachable from component k.
+struct Condensation<'a> {
+    graph: &'a Graph,
+    sets: &'a Sets,
+    ruletable: &'a Ruletable,
+    is_output: Array2D<bool>,
+    index: Vec<usize>,
+    lowlink: Vec<usize>,
+    on_stack: Vec<bool>,
+    component: Vec<usize>,
+    stack: Vec<usize>,
+    num_visited: usize,
+    reachable: BitMatrix,
+}
+
+impl<'a> Condensation<'a> {
+    fn new(graph: &'a Graph, sets: &'a Sets, ruletable: &'a Ruletable, n: usize) -> Self {
+        let mut is_output = Array2D::new(ruletable.num_edges(), ruletable.num_colors(), false);
+        for &(e, c) in ruletable.outputs() {
+            *is_output.get_mut(e, c) = true;
+        }
+        let num_states = n * ruletable.num_edges() * ruletable.num_colors();
+        Condensation {
+            graph,
+            sets,
+            ruletable,
+            is_output,
+            index: vec![usize::MAX; num_states],
+            lowlink: vec![0; num_states],
+            on_stack: vec![false; num_states],
+            component: vec![usize::MAX; num_states],
+            stack: Vec::new(),
+            num_visited: 0,
+            reachable: BitMatrix::new(0, n),
+        }
+    }
+
+    fn id(&self, s: State) -> usize {
+        (s.node * self.ruletable.num_edges() + s.edge) * self.ruletable.num_colors() + s.color
+    }
+
+    fn state(&self, id: usize) -> State {
+        let (num_edges, num_colors) = (self.ruletable.num_edges(), self.ruletable.num_colors());
+        State {
+            node: id / (num_edges * num_colors),
+            edge: id / num_colors % num_edges,
+            color: id % num_colors,
+        }
+    }
+
+    fn successors(&self, id: usize) -> Vec<usize> {
+        transitions(self.graph, self.sets, self.ruletable, self.state(id))
+            .map(|s| self.id(s))
+            .collect()
+    }
+
+    fn visit(&mut self, id: usize) -> (usize, Vec<usize>, usize) {
+        self.index[id] = self.num_visited;
+        self.lowlink[id] = self.num_visited;
+        self.num_visited += 1;
+        self.on_stack[id] = true;
+        self.stack.push(id);
+        (id, self.successors(id), 0)
+    }
+
+    // returns the component of the state, computing it first if necessary
+    fn component(&mut self, s: State) -> usize {
+        let root = self.id(s);
+        if self.index[root] != usize::MAX {
+            return self.component[root];
+        }
+        let mut calls = vec![self.visit(root)];
+        while let Some((id, successors, next)) = calls.last_mut() {
+            let id = *id;
+            if let Some(&successor) = successors.get(*next) {
+                *next += 1;
+                if self.index[successor] == usize::MAX {
+                    let call = self.visit(successor);
+                    calls.push(call);
+                } else if self.on_stack[successor] {
+                    self.lowlink[id] = cmp::min(self.lowlink[id], self.index[successor]);
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(parent, _, _)) = calls.last() {
+                self.lowlink[parent] = cmp::min(self.lowlink[parent], self.lowlink[id]);
+            }
+            if self.lowlink[id] == self.index[id] {
+                self.complete(id);
+            }
+        }
+        self.component[root]
+    }
+
+    // pops the component with root id from the stack and computes its reachable nodes
+    fn complete(&mut self, root: usize) {
+        let k = self.reachable.num_rows();
+        self.reachable.push_row();
+        let mut members = Vec::new();
+        while let Some(id) = self.stack.pop() {
+            self.on_stack[id] = false;
+            self.component[id] = k;
+            members.push(id);
+            if id == root {
+                break;
+            }
+        }
+        for &id in members.iter() {
+            let s = self.state(id);
+            if *self.is_output.get(s.edge, s.color) {
+                self.reachable.insert(k, s.node);
+            }
+            for successor in self.successors(id) {
+                let other = self.component[successor];
+                if other != k {
+                    self.reachable.union_own_row(k, other);
+                }
+            }
+        }
+    }
+}
+
+// start states in the order they are processed by reach, without duplicates
+pub(crate) fn start_states(sets: &Sets, ruletable: &Ruletable) -> Vec<State> {
+    let mut states = Vec::new();
+    let mut seen = HashSet::new();
+    for (set, e, c) in ruletable.starts().iter().copied() {
+        for v in sets.elements(set) {
+            let s = State {
+                node: v,
+                edge: e,
+                color: c,
+            };
+            if seen.insert(s) {
+                states.push(s);
+            }
+        }
+    }
+    states
+}
+
+// states reached from s1 by a single transition allowed by the rule table
+pub(crate) fn transitions<'a>(
+    graph: &'a Graph,
+    sets: &'a Sets,
+    ruletable: &'a Ruletable,
+    s1: State,
+) -> impl Iterator<Item = State> + 'a {
+    let neighbors = if s1.node < graph.num_vertices() {
+        graph.neighbors(s1.node)
+    } else {
+        &[]
+    };
+    neighbors.iter().flat_map(move |&(u2, t)| {
+        ruletable
+            .possible_colors(s1.edge, s1.color, t)
+            .iter()
+            .map(move |&c2| State {
+                node: u2,
+                edge: t,
+                color: c2,
+            })
+            .filter(move |&s2| ruletable.pass(sets, s1, s2))
+    })
+}
+
+pub(crate) fn is_output(ruletable: &Ruletable, s: State) -> bool {
+    ruletable.outputs().contains(&(s.edge, s.color))
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
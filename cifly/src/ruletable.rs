@@ -1,4 +1,10 @@
-use std::{cmp, collections::HashMap, error::Error, fmt, fs};
+use std::{
+    cmp,
+    collections::HashMap,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
 
 use crate::{
     array_nd::{Array3D, Array4D},
@@ -16,6 +22,7 @@ pub struct Ruletable {
     colors: HashMap<String, usize>,
     edges: HashMap<String, (usize, usize)>,
     sets: HashMap<String, usize>,
+    definitions: HashMap<String, String>,
     start: Vec<(usize, usize, usize)>,
     output: Vec<(usize, usize)>,
     rules: Vec<Rule>,
@@ -52,6 +59,15 @@ impl Ruletable {
         self.edges.get(s).copied()
     }
 
+    // the id of the reverse of every edge, such as <-- for -->
+    pub(crate) fn get_reverse_edge_ids(&self) -> Vec<usize> {
+        let mut reverse = vec![0; self.edges.len()];
+        self.edges
+            .values()
+            .for_each(|&(id, reverse_id)| reverse[id] = reverse_id);
+        reverse
+    }
+
     pub(crate) fn get_color_id(&self, s: &str) -> Option<usize> {
         self.colors.get(s).copied()
     }
@@ -67,6 +83,24 @@ impl Ruletable {
             .evaluate(sets, s1.node, s2.node)
     }
 
+    // the expression of the first rule matching the transition, None if no rule matches
+    pub(crate) fn rule_expression(
+        &self,
+        e1: usize,
+        c1: usize,
+        e2: usize,
+        c2: usize,
+    ) -> Option<&Expression> {
+        let rule_num = *self.to_rulenum.get(e1, c1, e2, c2);
+        self.rules.get(rule_num).map(|rule| &rule.expression)
+    }
+
+    pub(crate) fn uses_set(&self, set_id: usize) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.expression.uses_set(set_id))
+    }
+
     pub(crate) fn get_edge_strings(&self) -> Vec<String> {
         let mut edge_strings = vec!["".to_owned(); self.edges.len()];
         self.edges
@@ -85,29 +119,184 @@ impl Ruletable {
 
     /// Constructs a `Ruletable` from the contents of a file.
     ///
+    /// Paths in `INCLUDE` lines are resolved relative to the directory of the
+    /// including file.
+    ///
     /// # Errors
     /// Returns an error if the file cannot be read or if parsing fails.
     pub fn from_file(filename: &str) -> Result<Ruletable, ReadRuletableError> {
         let ruletable_str = fs::read_to_string(filename)?;
-        Self::from_multiline_string(&ruletable_str)
+        let path = Path::new(filename);
+        let lines = Self::resolve_includes(
+            Self::join_lines(&ruletable_str, None)?,
+            path.parent(),
+            &mut vec![fs::canonicalize(path)?],
+        )?;
+        Ok(Self::from_lines(lines)?)
     }
 
     /// Constructs a `Ruletable` from a multi-line string.
     ///
+    /// A line ending with `\` is continued on the next line and so is a line with
+    /// unclosed parentheses. Text after a `#` that is preceded by whitespace is a
+    /// trailing comment and ignored. Lines of the form `DEFINE name = expression`
+    /// introduce names that can be used inside rule expressions and are expanded
+    /// during parsing.
+    ///
+    /// A line `INCLUDE path` inserts the lines of another rule table, with `path`
+    /// resolved relative to the working directory. Rules before the `INCLUDE` line
+    /// take precedence over the included rules and rules after it only apply to
+    /// cases not matched by them. Declarations and definitions of the including
+    /// table replace the ones of the same kind, or name, in the included table.
+    ///
     /// # Errors
     /// Returns an error if parsing the input string fails.
     pub fn from_multiline_string(ruletable_str: &str) -> Result<Ruletable, ReadRuletableError> {
-        let lines = ruletable_str.split('\n');
+        let lines = Self::resolve_includes(
+            Self::join_lines(ruletable_str, None)?,
+            None,
+            &mut Vec::new(),
+        )?;
+        Ok(Self::from_lines(lines)?)
+    }
+
+    /// Returns the names of the sets declared in the `SETS` line, in declaration order.
+    pub fn set_names(&self) -> Vec<String> {
+        let mut set_strings = vec!["".to_owned(); self.sets.len()];
+        self.sets
+            .iter()
+            .for_each(|(k, &v)| set_strings[v] = k.clone());
+        set_strings
+    }
+
+    /// Returns the names of the edge types declared in the `EDGES` line, in declaration order.
+    ///
+    /// An edge type with a distinct reverse, such as `-->` with `<--`, is listed
+    /// before its reverse.
+    pub fn edge_names(&self) -> Vec<String> {
+        self.get_edge_strings()
+    }
+
+    /// Returns the name of the reverse of the edge type `edge`, such as `<--` for `-->`.
+    ///
+    /// Symmetric edge types such as `---` are their own reverse. Returns `None` if
+    /// `edge` is not declared in the rule table.
+    pub fn reverse_edge_name(&self, edge: &str) -> Option<String> {
+        let (_, reverse_id) = self.get_edge_ids(edge)?;
+        Some(self.get_edge_strings().swap_remove(reverse_id))
+    }
+
+    /// Returns the names of the colors declared in the `COLORS` line, in declaration order.
+    ///
+    /// The result is empty if the rule table has no `COLORS` line.
+    pub fn color_names(&self) -> Vec<String> {
+        self.get_color_strings()
+    }
+
+    /// Returns the start states specified by the `START` lines.
+    ///
+    /// Patterns such as `...` are expanded, so there is one entry for every
+    /// combination of a set, an edge type and a color.
+    pub fn start_specs(&self) -> Vec<StartSpec> {
+        let (edges, colors, sets) = (
+            self.get_edge_strings(),
+            self.get_color_strings(),
+            self.set_names(),
+        );
+        self.start
+            .iter()
+            .map(|&(set, edge, color)| StartSpec {
+                set: sets[set].clone(),
+                edge: edges[edge].clone(),
+                color: colors.get(color).cloned(),
+            })
+            .collect()
+    }
+
+    /// Returns the output states specified by the `OUTPUT` line, with patterns expanded.
+    pub fn output_specs(&self) -> Vec<OutputSpec> {
+        let (edges, colors) = (self.get_edge_strings(), self.get_color_strings());
+        self.output
+            .iter()
+            .map(|&(edge, color)| OutputSpec {
+                edge: edges[edge].clone(),
+                color: colors.get(color).cloned(),
+            })
+            .collect()
+    }
+
+    /// Returns the rules in order of precedence, with patterns expanded to the matched names.
+    ///
+    /// Definitions are already expanded in the returned expressions.
+    pub fn rule_specs(&self) -> Vec<RuleSpec> {
+        let (edges, colors) = (self.get_edge_strings(), self.get_color_strings());
+        let names = |pattern: &Pattern, names: &[String]| {
+            if names.is_empty() {
+                return Vec::new();
+            }
+            pattern
+                .convert_to_vec(names.len())
+                .into_iter()
+                .map(|i| names[i].clone())
+                .collect()
+        };
+        self.rules
+            .iter()
+            .map(|rule| RuleSpec {
+                prev_edges: names(&rule.case.prev_edge, &edges),
+                prev_colors: names(&rule.case.prev_color, &colors),
+                next_edges: names(&rule.case.next_edge, &edges),
+                next_colors: names(&rule.case.next_color, &colors),
+                expression: rule.expression.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the names of the sets used in `START` lines, in declaration order.
+    pub fn start_set_names(&self) -> Vec<String> {
+        self.set_names()
+            .into_iter()
+            .enumerate()
+            .filter(|&(id, _)| self.start.iter().any(|&(set, _, _)| set == id))
+            .map(|(_, name)| name)
+            .collect()
+    }
+
+    /// Returns the names of the sets referred to in rule expressions, in declaration order.
+    ///
+    /// Sets referred to in definitions count if the definitions are used in a rule.
+    pub fn expression_set_names(&self) -> Vec<String> {
+        self.set_names()
+            .into_iter()
+            .enumerate()
+            .filter(|&(id, _)| self.uses_set(id))
+            .map(|(_, name)| name)
+            .collect()
+    }
+
+    fn from_lines(mut lines: Vec<SourceLine>) -> Result<Ruletable, ParseRuletableError> {
+        // edges, colors and sets are needed for parsing all other lines and
+        // definitions for parsing rules, which may use definitions stated below them
+        lines.sort_by_key(|line| match Self::id_line(&line.content) {
+            LineType::Edges | LineType::Colors | LineType::Sets => 0,
+            LineType::Define => 1,
+            _ => 2,
+        });
+
         let mut ruletable = Self::new_empty();
-        for (line_number, raw_line) in lines.enumerate() {
-            let line = raw_line.trim();
+        let mut definition_lines = Vec::new();
+        for source_line in lines {
+            let line = source_line.content.as_str();
 
             let line_type = Self::id_line(line);
+            if let LineType::Define = line_type {
+                definition_lines.push(source_line.clone());
+            }
 
             ruletable.parse_line(line, &line_type).map_err(|err| {
                 ParseRuletableError(format!(
-                    "line {}: trying to parse a {} line: {} \n  {}",
-                    line_number + 1,
+                    "{}: trying to parse a {} line: {} \n  {}",
+                    source_line.location(),
                     line_type,
                     err.0,
                     line.chars().take(80).collect::<String>()
@@ -116,15 +305,183 @@ impl Ruletable {
             })?;
         }
 
+        // definitions are validated at the end as they may refer to definitions stated later
+        for source_line in definition_lines {
+            ruletable
+                .check_definition(&source_line.content)
+                .map_err(|err| {
+                    ParseRuletableError(format!(
+                        "{}: trying to parse a {} line: {}",
+                        source_line.location(),
+                        LineType::Define,
+                        err.0,
+                    ))
+                })?;
+        }
+
         ruletable.precompute();
         Ok(ruletable)
     }
 
+    fn resolve_includes(
+        lines: Vec<SourceLine>,
+        dir: Option<&Path>,
+        included_files: &mut Vec<PathBuf>,
+    ) -> Result<Vec<SourceLine>, ReadRuletableError> {
+        let declared: Vec<_> = lines
+            .iter()
+            .filter_map(|line| Self::declaration_key(&line.content))
+            .collect();
+
+        let mut resolved = Vec::new();
+        for source_line in lines {
+            let line_type = Self::id_line(&source_line.content);
+            if line_type != LineType::Include {
+                resolved.push(source_line);
+                continue;
+            }
+
+            let to_include = Self::get_remaining(
+                &source_line.content,
+                line_type
+                    .get_identifier()
+                    .expect("line type should have an identifier"),
+            )?;
+            let path = match dir {
+                Some(dir) => dir.join(&to_include),
+                None => PathBuf::from(&to_include),
+            };
+            let io_error = |err: io::Error| {
+                io::Error::new(
+                    err.kind(),
+                    format!(
+                        "{}: could not read included rule table '{}': {}",
+                        source_line.location(),
+                        path.display(),
+                        err
+                    ),
+                )
+            };
+            let canonical_path = fs::canonicalize(&path).map_err(io_error)?;
+            if included_files.contains(&canonical_path) {
+                return Err(ParseRuletableError(format!(
+                    "{}: found cyclic include of '{}'",
+                    source_line.location(),
+                    path.display()
+                ))
+                .into());
+            }
+            let ruletable_str = fs::read_to_string(&path).map_err(io_error)?;
+
+            included_files.push(canonical_path);
+            let included = Self::resolve_includes(
+                Self::join_lines(&ruletable_str, Some(&path.display().to_string()))?,
+                path.parent(),
+                included_files,
+            )?;
+            included_files.pop();
+
+            resolved.extend(included.into_iter().filter(|line| {
+                match Self::declaration_key(&line.content) {
+                    Some(key) => !declared.contains(&key),
+                    None => true,
+                }
+            }));
+        }
+        Ok(resolved)
+    }
+
+    // lines with the same key replace each other when including a rule table
+    fn declaration_key(line: &str) -> Option<String> {
+        let line_type = Self::id_line(line);
+        match line_type {
+            LineType::Edges
+            | LineType::Colors
+            | LineType::Sets
+            | LineType::Start
+            | LineType::Output => line_type.get_identifier().map(|s| s.to_owned()),
+            LineType::Define => {
+                let to_parse = Self::get_remaining(line, line_type.get_identifier()?).ok()?;
+                let (name, _) = Self::parse_definition(&to_parse).ok()?;
+                Some(format!("{} {}", line_type.get_identifier()?, name))
+            }
+            _ => None,
+        }
+    }
+
+    fn join_lines(
+        ruletable_str: &str,
+        file: Option<&str>,
+    ) -> Result<Vec<SourceLine>, ParseRuletableError> {
+        let mut lines = Vec::new();
+        let mut pending: Option<SourceLine> = None;
+        for (i, raw_line) in ruletable_str.split('\n').enumerate() {
+            let line = Self::strip_trailing_comment(raw_line).trim();
+            let line_number = i + 1;
+
+            let current = match pending.as_mut() {
+                Some(current) => {
+                    // empty lines and whole-line comments do not end a continued line
+                    if line.is_empty() {
+                        continue;
+                    }
+                    current.content.push(' ');
+                    current.content.push_str(line);
+                    current.last = line_number;
+                    current
+                }
+                None => pending.insert(SourceLine {
+                    file: file.map(|f| f.to_owned()),
+                    first: line_number,
+                    last: line_number,
+                    content: line.to_owned(),
+                }),
+            };
+
+            if let Some(stripped) = current.content.strip_suffix('\\') {
+                current.content = stripped.trim_end().to_owned();
+                continue;
+            }
+            if Self::count_open_parentheses(&current.content) > 0 {
+                continue;
+            }
+            lines.extend(pending.take());
+        }
+
+        match pending {
+            Some(current) => Err(ParseRuletableError(format!(
+                "{}: reached end of input while continuing line, check for a trailing '\\' or unclosed parentheses",
+                current.location()
+            ))),
+            None => Ok(lines),
+        }
+    }
+
+    fn strip_trailing_comment(line: &str) -> &str {
+        let mut previous = ' ';
+        for (pos, c) in line.char_indices() {
+            if c == '#' && previous.is_whitespace() {
+                return &line[..pos];
+            }
+            previous = c;
+        }
+        line
+    }
+
+    fn count_open_parentheses(s: &str) -> i64 {
+        s.chars().fold(0, |open, c| match c {
+            '(' => open + 1,
+            ')' => open - 1,
+            _ => open,
+        })
+    }
+
     fn new_empty() -> Ruletable {
         Ruletable {
             colors: HashMap::new(),
             edges: HashMap::new(),
             sets: HashMap::new(),
+            definitions: HashMap::new(),
             start: Vec::new(),
             output: Vec::new(),
             rules: Vec::new(),
@@ -144,6 +501,8 @@ impl Ruletable {
             LineType::Sets,
             LineType::Start,
             LineType::Output,
+            LineType::Define,
+            LineType::Include,
         ];
         for line_type in identifier_lines {
             if line.starts_with(
@@ -171,11 +530,24 @@ impl Ruletable {
 
         match line_type {
             LineType::Empty | LineType::Comment => (),
+            LineType::Include => {
+                return Err(ParseRuletableError(
+                    "found unresolved include, includes are resolved before parsing".to_owned(),
+                ))
+            }
             LineType::Edges => self.edges = Self::parse_edges(&to_parse)?,
             LineType::Colors => self.colors = Self::parse_labels(&to_parse, "colors")?,
             LineType::Sets => self.sets = Self::parse_labels(&to_parse, "sets")?,
             LineType::Start => self.start.append(&mut self.parse_start(&to_parse)?),
             LineType::Output => self.output.append(&mut self.parse_output(&to_parse)?),
+            LineType::Define => {
+                let (name, body) = Self::parse_definition(&to_parse)?;
+                if self.definitions.insert(name.clone(), body).is_some() {
+                    return Err(ParseRuletableError(format!(
+                        "found definition of '{name}' twice"
+                    )));
+                }
+            }
             LineType::Rule => self.rules.push(self.parse_rule(&to_parse)?),
         }
         Ok(())
@@ -296,6 +668,47 @@ impl Ruletable {
         Ok(Rule { case, expression })
     }
 
+    fn parse_definition(s: &str) -> Result<(String, String), ParseRuletableError> {
+        let (name, body) = s.split_once('=').ok_or_else(|| {
+            ParseRuletableError(
+                "did not find '=', expected a name followed by '=' and an expression".to_owned(),
+            )
+        })?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ParseRuletableError(
+                "found empty string, expected a name before '='".to_owned(),
+            ));
+        }
+        if name.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+            return Err(ParseRuletableError(format!(
+                "found name '{name}' containing whitespace or braces, expected a single word"
+            )));
+        }
+        if Expression::is_keyword(name) {
+            return Err(ParseRuletableError(format!(
+                "found reserved keyword '{name}', expected a name that is not used in expressions"
+            )));
+        }
+        Ok((name.to_owned(), body.trim().to_owned()))
+    }
+
+    fn check_definition(&self, s: &str) -> Result<(), ParseRuletableError> {
+        let (name, body) = Self::parse_definition(&Self::get_remaining(
+            s,
+            LineType::Define
+                .get_identifier()
+                .expect("line type should have an identifier"),
+        )?)?;
+        if self.sets.contains_key(&name) {
+            return Err(ParseRuletableError(format!(
+                "found name '{name}' which is already declared as a set"
+            )));
+        }
+        self.parse_expression(&body)?;
+        Ok(())
+    }
+
     fn parse_edge_color_patterns(
         &self,
         s: &str,
@@ -392,7 +805,7 @@ impl Ruletable {
     }
 
     fn parse_expression(&self, s: &str) -> Result<Expression, ParseRuletableError> {
-        Ok(Expression::from_string(s, &self.sets)?)
+        Ok(Expression::from_string(s, &self.sets, &self.definitions)?)
     }
 
     fn find_edge(&self, s: &str) -> Result<usize, ParseRuletableError> {
@@ -497,11 +910,66 @@ impl Ruletable {
     }
 }
 
+#[derive(Clone)]
+struct SourceLine {
+    file: Option<String>,
+    first: usize,
+    last: usize,
+    content: String,
+}
+
+impl SourceLine {
+    fn location(&self) -> String {
+        let lines = if self.first == self.last {
+            format!("line {}", self.first)
+        } else {
+            format!("lines {}-{}", self.first, self.last)
+        };
+        match &self.file {
+            Some(file) => format!("{file}, {lines}"),
+            None => lines,
+        }
+    }
+}
+
 pub struct Rule {
     case: Case,
     expression: Expression,
 }
 
+/// Start state of a rule table, as returned by [`Ruletable::start_specs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartSpec {
+    pub set: String,
+    pub edge: String,
+    /// The color, or `None` if the rule table has no colors.
+    pub color: Option<String>,
+}
+
+/// Output state of a rule table, as returned by [`Ruletable::output_specs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputSpec {
+    pub edge: String,
+    /// The color, or `None` if the rule table has no colors.
+    pub color: Option<String>,
+}
+
+/// Rule of a rule table, as returned by [`Ruletable::rule_specs`].
+///
+/// The rule applies to a transition from a previous to a next state if their
+/// edge types and colors are contained in the respective lists. The color lists
+/// are empty if the rule table has no colors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSpec {
+    pub prev_edges: Vec<String>,
+    pub prev_colors: Vec<String>,
+    pub next_edges: Vec<String>,
+    pub next_colors: Vec<String>,
+    /// The expression deciding whether the transition is allowed, with sets
+    /// referred to by their position in [`Ruletable::set_names`].
+    pub expression: Expression,
+}
+
 struct Case {
     prev_edge: Pattern,
     prev_color: Pattern,
@@ -537,7 +1005,7 @@ impl Pattern {
     fn is_matched(&self, found: usize) -> bool {
         match self {
             Pattern::Single(p) => *p == found,
-            Pattern::Many(ps) => ps.iter().any(|&p| p == found),
+            Pattern::Many(ps) => ps.contains(&found),
             Pattern::All => true,
         }
     }
@@ -600,6 +1068,7 @@ impl From<ParseExpressionError> for ParseRuletableError {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum LineType {
     Empty,
     Comment,
@@ -608,6 +1077,8 @@ enum LineType {
     Sets,
     Start,
     Output,
+    Define,
+    Include,
     Rule,
 }
 
@@ -621,6 +1092,8 @@ impl fmt::Display for LineType {
             LineType::Sets => write!(f, "set declaration"),
             LineType::Start => write!(f, "start declaration"),
             LineType::Output => write!(f, "output declaration"),
+            LineType::Define => write!(f, "definition"),
+            LineType::Include => write!(f, "include"),
             LineType::Rule => write!(f, "rule declaration"),
         }
     }
@@ -636,6 +1109,8 @@ impl LineType {
             LineType::Sets => Some("SETS"),
             LineType::Start => Some("START"),
             LineType::Output => Some("OUTPUT"),
+            LineType::Define => Some("DEFINE"),
+            LineType::Include => Some("INCLUDE"),
         }
     }
 }
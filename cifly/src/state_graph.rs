@@ -0,0 +1,219 @@
+//! Materializing the state graph searched by [`reach`](crate::reach::reach).
+//!
+//! The search in `reach` runs on an implicit graph whose states are triples of a
+//! node, the edge type the node was reached by and a color, with a transition
+//! between two states if the rule table allows it. This module builds this graph
+//! explicitly, which is useful for explaining rule tables and for finding out why
+//! a state is not reached, and exports it in the DOT and GraphML formats.
+
+use std::{
+    cmp,
+    collections::{HashMap, VecDeque},
+    fmt::Write,
+};
+
+use crate::{
+    instance::{Graph, Sets},
+    reach::{is_output, start_states, transitions, State},
+    ruletable::Ruletable,
+};
+
+/// State of the state graph with the names of its edge type and color.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateInfo {
+    pub node: usize,
+    pub edge: String,
+    /// The color of the state, or `None` if the rule table has no colors.
+    pub color: Option<String>,
+    pub is_start: bool,
+    pub is_output: bool,
+    /// Whether the state can be reached from a start state.
+    pub is_reachable: bool,
+}
+
+impl StateInfo {
+    /// Returns the state as `(node, edge)` or `(node, edge, color)`.
+    pub fn label(&self) -> String {
+        match &self.color {
+            Some(color) => format!("({}, {}, {})", self.node, self.edge, color),
+            None => format!("({}, {})", self.node, self.edge),
+        }
+    }
+}
+
+/// State graph of a `Graph`, `Sets` and `Ruletable`, as returned by [`state_graph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateGraph {
+    /// All states ordered by node, then edge type and then color.
+    pub states: Vec<StateInfo>,
+    /// The indices of the states reached from each state by one transition, in ascending order.
+    pub adjacency: Vec<Vec<usize>>,
+    // index of each state by node, edge type and color, for find
+    index: HashMap<(usize, String, Option<String>), usize>,
+}
+
+/// Builds the state graph that [`reach`](crate::reach::reach) searches.
+///
+/// Contains a state for every combination of a node, an edge type and a color,
+/// including unreachable ones, for all nodes of the graph and the sets. The
+/// states are marked as start states, output states and reachable states, such
+/// that the reachable output states are exactly those found by `reach`. As there
+/// are states for all combinations, the state graph is only suited for small
+/// graphs.
+pub fn state_graph(graph: &Graph, sets: &Sets, ruletable: &Ruletable) -> StateGraph {
+    let num_nodes = cmp::max(graph.num_vertices(), sets.max_size());
+    let (num_edges, num_colors) = (ruletable.num_edges(), ruletable.num_colors());
+    let index = |s: State| (s.node * num_edges + s.edge) * num_colors + s.color;
+    let edge_strings = ruletable.get_edge_strings();
+    let color_strings = ruletable.get_color_strings();
+
+    let mut states = Vec::with_capacity(num_nodes * num_edges * num_colors);
+    let mut adjacency = Vec::with_capacity(states.capacity());
+    for node in 0..num_nodes {
+        for (edge, edge_string) in edge_strings.iter().enumerate() {
+            for color in 0..num_colors {
+                let s = State { node, edge, color };
+                states.push(StateInfo {
+                    node,
+                    edge: edge_string.clone(),
+                    color: color_strings.get(color).cloned(),
+                    is_start: false,
+                    is_output: is_output(ruletable, s),
+                    is_reachable: false,
+                });
+                let mut successors: Vec<_> =
+                    transitions(graph, sets, ruletable, s).map(index).collect();
+                successors.sort_unstable();
+                successors.dedup();
+                adjacency.push(successors);
+            }
+        }
+    }
+
+    let mut queue = VecDeque::new();
+    for s in start_states(sets, ruletable) {
+        let i = index(s);
+        states[i].is_start = true;
+        states[i].is_reachable = true;
+        queue.push_back(i);
+    }
+    while let Some(i) = queue.pop_front() {
+        for &j in adjacency[i].iter() {
+            if !states[j].is_reachable {
+                states[j].is_reachable = true;
+                queue.push_back(j);
+            }
+        }
+    }
+    let index = states
+        .iter()
+        .enumerate()
+        .map(|(i, s)| ((s.node, s.edge.clone(), s.color.clone()), i))
+        .collect();
+    StateGraph {
+        states,
+        adjacency,
+        index,
+    }
+}
+
+impl StateGraph {
+    /// Returns the index of the state with the given node, edge type and color.
+    pub fn find(&self, node: usize, edge: &str, color: Option<&str>) -> Option<usize> {
+        self.index
+            .get(&(node, edge.to_owned(), color.map(str::to_owned)))
+            .copied()
+    }
+
+    /// Exports the state graph in the DOT format of Graphviz.
+    ///
+    /// Start states are drawn bold, output states with a double border and
+    /// unreachable states in gray.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph states {\n");
+        for (i, s) in self.states.iter().enumerate() {
+            let mut attributes = vec![format!("label=\"{}\"", escape_dot(&s.label()))];
+            if s.is_start {
+                attributes.push("style=bold".to_owned());
+            }
+            if s.is_output {
+                attributes.push("peripheries=2".to_owned());
+            }
+            if !s.is_reachable {
+                attributes.push("color=gray".to_owned());
+                attributes.push("fontcolor=gray".to_owned());
+            }
+            writeln!(dot, "  {} [{}];", i, attributes.join(", ")).unwrap();
+        }
+        for (i, successors) in self.adjacency.iter().enumerate() {
+            for j in successors.iter() {
+                writeln!(dot, "  {} -> {};", i, j).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Exports the state graph in the GraphML format.
+    ///
+    /// The node, edge type and color of every state as well as whether it is a
+    /// start, output and reachable state are stored as data attributes.
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"node\" for=\"node\" attr.name=\"node\" attr.type=\"int\"/>\n",
+            "  <key id=\"edge\" for=\"node\" attr.name=\"edge\" attr.type=\"string\"/>\n",
+            "  <key id=\"color\" for=\"node\" attr.name=\"color\" attr.type=\"string\"/>\n",
+            "  <key id=\"start\" for=\"node\" attr.name=\"start\" attr.type=\"boolean\"/>\n",
+            "  <key id=\"output\" for=\"node\" attr.name=\"output\" attr.type=\"boolean\"/>\n",
+            "  <key id=\"reachable\" for=\"node\" attr.name=\"reachable\" attr.type=\"boolean\"/>\n",
+            "  <graph id=\"states\" edgedefault=\"directed\">\n",
+        ));
+        for (i, s) in self.states.iter().enumerate() {
+            writeln!(xml, "    <node id=\"s{}\">", i).unwrap();
+            writeln!(xml, "      <data key=\"node\">{}</data>", s.node).unwrap();
+            writeln!(
+                xml,
+                "      <data key=\"edge\">{}</data>",
+                escape_xml(&s.edge)
+            )
+            .unwrap();
+            if let Some(color) = &s.color {
+                writeln!(
+                    xml,
+                    "      <data key=\"color\">{}</data>",
+                    escape_xml(color)
+                )
+                .unwrap();
+            }
+            writeln!(xml, "      <data key=\"start\">{}</data>", s.is_start).unwrap();
+            writeln!(xml, "      <data key=\"output\">{}</data>", s.is_output).unwrap();
+            writeln!(
+                xml,
+                "      <data key=\"reachable\">{}</data>",
+                s.is_reachable
+            )
+            .unwrap();
+            xml.push_str("    </node>\n");
+        }
+        for (i, successors) in self.adjacency.iter().enumerate() {
+            for j in successors.iter() {
+                writeln!(xml, "    <edge source=\"s{}\" target=\"s{}\"/>", i, j).unwrap();
+            }
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
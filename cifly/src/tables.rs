@@ -0,0 +1,69 @@
+//! Rule tables bundled with CIfly.
+//!
+//! These are the rule tables from the `ruletables` directory of the CIfly repository,
+//! which are used by the example algorithms. Each table can be obtained as parsed
+//! [`Ruletable`], for instance with [`Ruletable::dsep`], or by name with
+//! [`Ruletable::bundled`]. The sources are available through [`source`].
+
+use crate::ruletable::Ruletable;
+
+macro_rules! bundled_tables {
+    ($($name:ident => $file:literal,)*) => {
+        /// Names of all bundled rule tables, in alphabetical order.
+        pub const NAMES: &[&str] = &[$(stringify!($name)),*];
+
+        /// Returns the source of the bundled rule table `name`, or `None` if there is
+        /// no such table.
+        pub fn source(name: &str) -> Option<&'static str> {
+            match name {
+                $(stringify!($name) => Some(include_str!(concat!("../ruletables/", $file))),)*
+                _ => None,
+            }
+        }
+
+        impl Ruletable {
+            $(
+                #[doc = concat!("Returns the bundled rule table `", $file, "`.")]
+                pub fn $name() -> Ruletable {
+                    Self::bundled(stringify!($name)).expect("bundled rule table should exist")
+                }
+            )*
+        }
+    };
+}
+
+bundled_tables! {
+    ancestors_admg => "ancestors_admg.txt",
+    ancestors_subgraph_admg => "ancestors_subgraph_admg.txt",
+    backdoor_connected_cpdag => "backdoor_connected_cpdag.txt",
+    backdoor_connected_dag => "backdoor_connected_dag.txt",
+    closure_admg => "closure_admg.txt",
+    dconnected_admg => "dconnected_admg.txt",
+    dconnected_marks_pag => "dconnected_marks_pag.txt",
+    dconnected_paths_admg => "dconnected_paths_admg.txt",
+    descendants_admg => "descendants_admg.txt",
+    district_admg => "district_admg.txt",
+    dsep => "dsep.txt",
+    forbidden_path_connected_cpdag => "forbidden_path_connected_cpdag.txt",
+    frontdoor_forbidden_dag => "frontdoor_forbidden_dag.txt",
+    intercepted_paths_dag => "intercepted_paths_dag.txt",
+    non_causal_connected_cpdag => "non_causal_connected_cpdag.txt",
+    not_amenable_cpdag => "not_amenable_cpdag.txt",
+    optimal_iv_admg => "optimal_iv_admg.txt",
+    parents_cpdag => "parents_cpdag.txt",
+    possible_ancestors_cpdag => "possible_ancestors_cpdag.txt",
+    possible_ancestors_pag => "possible_ancestors_pag.txt",
+    possible_descendants_cpdag => "possible_descendants_cpdag.txt",
+    possible_descendants_pag => "possible_descendants_pag.txt",
+}
+
+impl Ruletable {
+    /// Returns the bundled rule table `name`, or `None` if there is no such table.
+    ///
+    /// See [`NAMES`] for the available tables.
+    pub fn bundled(name: &str) -> Option<Ruletable> {
+        source(name).map(|ruletable_str| {
+            Self::from_multiline_string(ruletable_str).expect("bundled rule table should parse")
+        })
+    }
+}
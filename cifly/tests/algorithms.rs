@@ -0,0 +1,1083 @@
+use std::collections::{BTreeSet, HashMap};
+
+use cifly::{
+    algorithms::{self, EdgeLists},
+    estimand::Estimand,
+    reach::{reach, Settings},
+    Graph, Ruletable, Sets,
+};
+
+fn graph(edges: &[(&str, &[(usize, usize)])]) -> EdgeLists {
+    edges
+        .iter()
+        .map(|&(edge_type, list)| (edge_type.to_owned(), list.to_vec()))
+        .collect::<HashMap<_, _>>()
+}
+
+#[test]
+fn test_cpdag_adjustment() {
+    let cpdag = graph(&[
+        ("-->", &[(0, 2), (1, 2), (2, 5), (3, 2), (3, 5), (4, 5)]),
+        ("---", &[(0, 1), (1, 3), (1, 4), (3, 4)]),
+    ]);
+    let is_adjustment = |w: &[usize]| algorithms::is_cpdag_adjustment(&cpdag, &[2], &[5], w);
+
+    assert!(is_adjustment(&[1, 3]).unwrap());
+    assert!(is_adjustment(&[3, 4]).unwrap());
+    assert!(is_adjustment(&[0, 1, 3, 4]).unwrap());
+    assert!(!is_adjustment(&[1]).unwrap());
+    assert!(!is_adjustment(&[]).unwrap());
+
+    let cpdag = graph(&[("-->", &[(1, 2), (2, 4), (3, 2)]), ("---", &[(0, 1)])]);
+    assert!(algorithms::is_cpdag_adjustment(&cpdag, &[0, 4], &[3], &[1, 2]).unwrap());
+    assert!(!algorithms::is_cpdag_adjustment(&cpdag, &[0, 4], &[3], &[2]).unwrap());
+}
+
+#[test]
+fn test_optimal_adjustment() {
+    let cpdag = graph(&[
+        ("-->", &[(1, 4), (3, 4), (2, 4)]),
+        ("---", &[(0, 1), (0, 3), (1, 3)]),
+    ]);
+    assert_eq!(
+        algorithms::optimal_adjustment(&cpdag, &[2], &[4]).unwrap(),
+        Some(vec![1, 3])
+    );
+    assert_eq!(
+        algorithms::optimal_adjustment(&cpdag, &[3], &[4]).unwrap(),
+        None
+    );
+    assert_eq!(
+        algorithms::optimal_adjustment(&cpdag, &[4], &[2]).unwrap(),
+        None
+    );
+
+    let dag = graph(&[("-->", &[(0, 1), (1, 2), (2, 3), (2, 4)])]);
+    assert_eq!(
+        algorithms::optimal_adjustment(&dag, &[0], &[3]).unwrap(),
+        Some(vec![])
+    );
+    assert_eq!(
+        algorithms::optimal_adjustment(&dag, &[0, 4], &[3]).unwrap(),
+        None
+    );
+
+    let admg = graph(&[("-->", &[(0, 1)]), ("<->", &[(0, 1)])]);
+    assert!(algorithms::optimal_adjustment(&admg, &[0], &[1]).is_err());
+}
+
+// lists the valid and the minimal adjustment sets among the subsets of candidates
+// satisfying the options, using the adjustment check on a CPDAG
+fn brute_force_adjustment(
+    cpdag: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    candidates: &[usize],
+    options: &algorithms::AdjustmentOptions,
+) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+    let is_valid = |z: &[usize]| {
+        options.required.iter().all(|v| z.contains(v))
+            && !options.forbidden.iter().any(|v| z.contains(v))
+            && algorithms::is_cpdag_adjustment(cpdag, x, y, z).unwrap()
+    };
+    let mut valid = Vec::new();
+    let mut minimal = Vec::new();
+    for mask in 0..(1 << candidates.len()) {
+        let z: Vec<usize> = (0..candidates.len())
+            .filter(|k| mask & (1 << k) != 0)
+            .map(|k| candidates[k])
+            .collect();
+        if !is_valid(&z) {
+            continue;
+        }
+        let is_minimal = z.iter().filter(|v| !options.required.contains(v)).all(|v| {
+            let smaller: Vec<usize> = z.iter().copied().filter(|u| u != v).collect();
+            !is_valid(&smaller)
+        });
+        if options.max_size.is_some_and(|k| z.len() > k) {
+            continue;
+        }
+        if is_minimal {
+            minimal.push(z.clone());
+        }
+        valid.push(z);
+    }
+    valid.sort();
+    minimal.sort();
+    (valid, minimal)
+}
+
+fn enumerate_adjustment(
+    graph: &EdgeLists,
+    x: &[usize],
+    y: &[usize],
+    options: &algorithms::AdjustmentOptions,
+) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+    let mut valid: Vec<_> = algorithms::enumerate_adjustment_sets(graph, x, y, options)
+        .unwrap()
+        .collect();
+    let minimal_options = algorithms::AdjustmentOptions {
+        minimal: true,
+        ..options.clone()
+    };
+    let mut minimal: Vec<_> = algorithms::enumerate_adjustment_sets(graph, x, y, &minimal_options)
+        .unwrap()
+        .collect();
+    valid.sort();
+    minimal.sort();
+    (valid, minimal)
+}
+
+#[test]
+fn test_enumerate_adjustment_sets() {
+    let options = algorithms::AdjustmentOptions::default();
+    let cpdag = graph(&[
+        ("-->", &[(0, 2), (1, 2), (2, 5), (3, 2), (3, 5), (4, 5)]),
+        ("---", &[(0, 1), (1, 3), (1, 4), (3, 4)]),
+    ]);
+    let (valid, minimal) = enumerate_adjustment(&cpdag, &[2], &[5], &options);
+    assert_eq!(minimal, vec![vec![1, 3], vec![3, 4]]);
+    assert_eq!(
+        (valid, minimal),
+        brute_force_adjustment(&cpdag, &[2], &[5], &[0, 1, 3, 4], &options)
+    );
+
+    let cpdag = graph(&[
+        ("-->", &[(1, 4), (3, 4), (2, 4)]),
+        ("---", &[(0, 1), (0, 3), (1, 3)]),
+    ]);
+    assert_eq!(
+        enumerate_adjustment(&cpdag, &[2], &[4], &options),
+        brute_force_adjustment(&cpdag, &[2], &[4], &[0, 1, 3], &options)
+    );
+    // not amenable
+    assert_eq!(
+        enumerate_adjustment(&cpdag, &[3], &[4], &options),
+        (vec![], vec![])
+    );
+
+    let options = algorithms::AdjustmentOptions {
+        max_size: Some(3),
+        required: vec![0],
+        forbidden: vec![4],
+        ..Default::default()
+    };
+    let cpdag = graph(&[
+        ("-->", &[(0, 2), (1, 2), (2, 5), (3, 2), (3, 5), (4, 5)]),
+        ("---", &[(0, 1), (1, 3), (1, 4), (3, 4)]),
+    ]);
+    let (valid, minimal) = enumerate_adjustment(&cpdag, &[2], &[5], &options);
+    assert_eq!(valid, vec![vec![0, 1, 3]]);
+    assert_eq!(minimal, vec![vec![0, 1, 3]]);
+}
+
+// compares DAGs against the adjustment check and ADMGs against the adjustment
+// check on the DAG with a latent parent for each bidirected edge
+#[test]
+fn test_enumerate_adjustment_sets_brute_force() {
+    let (num_nodes, x, y) = (7, 1, 5);
+    let mut seed: u64 = 7;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+
+    for round in 0..100 {
+        let mut directed = Vec::new();
+        let mut bidirected = Vec::new();
+        for u in 0..num_nodes {
+            for v in (u + 1)..num_nodes {
+                match random(7) {
+                    0 | 1 => directed.push((u, v)),
+                    2 if round % 2 == 1 => bidirected.push((u, v)),
+                    _ => {}
+                }
+            }
+        }
+        let mut latent_dag = directed.clone();
+        for (k, &(u, v)) in bidirected.iter().enumerate() {
+            latent_dag.extend([(num_nodes + k, u), (num_nodes + k, v)]);
+        }
+        let admg = graph(&[("-->", &directed), ("<->", &bidirected)]);
+        let latent_dag = graph(&[("-->", &latent_dag)]);
+
+        let options = algorithms::AdjustmentOptions {
+            max_size: (random(3) == 0).then_some(2),
+            required: if random(3) == 0 { vec![3] } else { vec![] },
+            forbidden: if random(3) == 0 { vec![0] } else { vec![] },
+            ..Default::default()
+        };
+        // the enumeration only knows the nodes up to the largest one mentioned
+        let largest = directed
+            .iter()
+            .chain(&bidirected)
+            .flat_map(|&(u, v)| [u, v])
+            .chain(options.required.iter().copied())
+            .fold(y, usize::max);
+        let candidates: Vec<usize> = (0..=largest).filter(|&v| v != x && v != y).collect();
+        assert_eq!(
+            enumerate_adjustment(&admg, &[x], &[y], &options),
+            brute_force_adjustment(&latent_dag, &[x], &[y], &candidates, &options),
+            "graph {:?}, options {:?}",
+            admg,
+            options
+        );
+    }
+}
+
+// CPDAG of a DAG with few edges, computed from all orientations of its skeleton
+fn brute_force_cpdag(num_nodes: usize, dag: &[(usize, usize)]) -> EdgeLists {
+    let is_acyclic = |edges: &[(usize, usize)]| {
+        let mut in_degree = vec![0; num_nodes];
+        for &(_, v) in edges {
+            in_degree[v] += 1;
+        }
+        let mut stack: Vec<usize> = (0..num_nodes).filter(|&v| in_degree[v] == 0).collect();
+        let mut visited = 0;
+        while let Some(u) = stack.pop() {
+            visited += 1;
+            for &(_, v) in edges.iter().filter(|&&(a, _)| a == u) {
+                in_degree[v] -= 1;
+                if in_degree[v] == 0 {
+                    stack.push(v);
+                }
+            }
+        }
+        visited == num_nodes
+    };
+    let v_structures = |edges: &[(usize, usize)]| {
+        let adjacent = |a: usize, b: usize| {
+            edges
+                .iter()
+                .any(|&(u, v)| (u, v) == (a, b) || (u, v) == (b, a))
+        };
+        let mut found = BTreeSet::new();
+        for &(a, c) in edges {
+            for &(b, d) in edges {
+                if c == d && a < b && !adjacent(a, b) {
+                    found.insert((a, b, c));
+                }
+            }
+        }
+        found
+    };
+
+    let expected = v_structures(dag);
+    let mut orientations = vec![BTreeSet::new(); dag.len()];
+    for mask in 0..(1 << dag.len()) {
+        let edges: Vec<_> = dag
+            .iter()
+            .enumerate()
+            .map(|(k, &(u, v))| if mask & (1 << k) != 0 { (v, u) } else { (u, v) })
+            .collect();
+        if is_acyclic(&edges) && v_structures(&edges) == expected {
+            for (k, &edge) in edges.iter().enumerate() {
+                orientations[k].insert(edge);
+            }
+        }
+    }
+    let (directed, undirected): (Vec<_>, Vec<_>) = orientations
+        .iter()
+        .zip(dag)
+        .partition(|(orientation, _)| orientation.len() == 1);
+    HashMap::from([
+        (
+            "-->".to_owned(),
+            directed.into_iter().map(|(_, &edge)| edge).collect(),
+        ),
+        (
+            "---".to_owned(),
+            undirected.into_iter().map(|(_, &edge)| edge).collect(),
+        ),
+    ])
+}
+
+#[test]
+fn test_enumerate_adjustment_sets_cpdag_brute_force() {
+    let num_nodes = 6;
+    let mut seed: u64 = 11;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+
+    let options = algorithms::AdjustmentOptions::default();
+    for _ in 0..100 {
+        let mut dag = Vec::new();
+        for u in 0..num_nodes {
+            for v in (u + 1)..num_nodes {
+                if random(5) < 2 {
+                    dag.push((u, v));
+                }
+            }
+        }
+        let cpdag = brute_force_cpdag(num_nodes, &dag);
+        let (x, y) = (random(3) as usize, 3 + random(3) as usize);
+        // the enumeration only knows the nodes up to the largest one mentioned
+        let largest = dag.iter().flat_map(|&(u, v)| [u, v]).fold(y, usize::max);
+        let candidates: Vec<usize> = (0..=largest).filter(|&v| v != x && v != y).collect();
+        assert_eq!(
+            enumerate_adjustment(&cpdag, &[x], &[y], &options),
+            brute_force_adjustment(&cpdag, &[x], &[y], &candidates, &options),
+            "graph {:?}, x {}, y {}",
+            cpdag,
+            x,
+            y
+        );
+    }
+}
+
+#[test]
+fn test_pag_adjustment() {
+    // a o-> x --> y with a visible edge x --> y
+    let pag = graph(&[("o->", &[(0, 1)]), ("-->", &[(1, 2)])]);
+    assert!(algorithms::is_pag_amenable(&pag, &[1], &[2]).unwrap());
+    assert!(algorithms::is_pag_adjustment(&pag, &[1], &[2], &[]).unwrap());
+    assert!(algorithms::is_pag_adjustment(&pag, &[1], &[2], &[0]).unwrap());
+    assert_eq!(
+        algorithms::pag_adjustment(&pag, &[1], &[2]).unwrap(),
+        Some(vec![0])
+    );
+
+    let pag = graph(&[("o-o", &[(0, 1)])]);
+    assert!(!algorithms::is_pag_amenable(&pag, &[0], &[1]).unwrap());
+    assert_eq!(algorithms::pag_adjustment(&pag, &[0], &[1]).unwrap(), None);
+
+    // the edge x --> y of a MAG is invisible without a further parent of x
+    let mag = graph(&[("-->", &[(0, 1)])]);
+    assert!(!algorithms::is_pag_amenable(&mag, &[0], &[1]).unwrap());
+
+    // a --> x --> y with backdoor path x <-> b --> y
+    let mag = graph(&[("-->", &[(0, 2), (1, 3), (2, 3)]), ("<->", &[(1, 2)])]);
+    let is_adjustment = |z: &[usize]| algorithms::is_pag_adjustment(&mag, &[2], &[3], z).unwrap();
+    assert!(is_adjustment(&[1]));
+    assert!(is_adjustment(&[0, 1]));
+    assert!(!is_adjustment(&[]));
+    assert!(!is_adjustment(&[0]));
+    assert!(!is_adjustment(&[1, 3]));
+    assert_eq!(
+        algorithms::pag_adjustment(&mag, &[2], &[3]).unwrap(),
+        Some(vec![0, 1])
+    );
+
+    // x --> y is visible due to the collider path c --> v <-> x with v --> y
+    let mag = graph(&[("-->", &[(0, 1), (1, 3), (2, 3)]), ("<->", &[(1, 2)])]);
+    assert!(algorithms::is_pag_amenable(&mag, &[2], &[3]).unwrap());
+    assert!(algorithms::is_pag_adjustment(&mag, &[2], &[3], &[1]).unwrap());
+    assert!(!algorithms::is_pag_adjustment(&mag, &[2], &[3], &[0]).unwrap());
+    assert_eq!(
+        algorithms::pag_adjustment(&mag, &[2], &[3]).unwrap(),
+        Some(vec![0, 1])
+    );
+    let mag = graph(&[("-->", &[(1, 3), (2, 3)]), ("<->", &[(1, 2)])]);
+    assert!(!algorithms::is_pag_amenable(&mag, &[2], &[3]).unwrap());
+
+    assert!(algorithms::is_pag_adjustment(&graph(&[("---", &[(0, 1)])]), &[0], &[1], &[]).is_err());
+}
+
+#[test]
+fn test_pag_tables() {
+    let pag = graph(&[
+        ("o-o", &[(0, 1), (1, 2)]),
+        ("-->", &[(2, 3)]),
+        ("o->", &[(4, 3)]),
+    ]);
+    let reach_from = |ruletable: Ruletable, sets: &[(&str, Vec<usize>)]| {
+        let g = Graph::new(&pag, &ruletable).unwrap();
+        let sets = sets
+            .iter()
+            .map(|(name, set)| (name.to_string(), set.clone()))
+            .collect();
+        let mut reached = reach(
+            &g,
+            &Sets::new(&sets, &ruletable).unwrap(),
+            &ruletable,
+            &Settings::default(),
+        );
+        reached.sort();
+        reached
+    };
+
+    assert_eq!(
+        reach_from(Ruletable::possible_descendants_pag(), &[("X", vec![0])]),
+        vec![0, 1, 2, 3]
+    );
+    assert_eq!(
+        reach_from(Ruletable::possible_ancestors_pag(), &[("X", vec![3])]),
+        vec![0, 1, 2, 3, 4]
+    );
+    // 1 is a non-collider of the unshielded triple 0 o-o 1 o-o 2, which the
+    // table cannot tell apart from a shielded one, and 3 is a collider
+    assert_eq!(
+        reach_from(
+            Ruletable::dconnected_marks_pag(),
+            &[("X", vec![0]), ("Z", vec![])]
+        ),
+        vec![0, 1]
+    );
+    assert_eq!(
+        reach_from(
+            Ruletable::dconnected_marks_pag(),
+            &[("X", vec![2]), ("Z", vec![3])]
+        ),
+        vec![1, 2, 3, 4]
+    );
+}
+
+#[test]
+fn test_pag_m_connected() {
+    let pag = graph(&[
+        ("o-o", &[(0, 1), (1, 2)]),
+        ("-->", &[(2, 3)]),
+        ("o->", &[(4, 3)]),
+    ]);
+    let connected = |x: usize, y: usize, z: &[usize]| {
+        algorithms::is_pag_m_connected(&pag, &[x], &[y], z).unwrap()
+    };
+    assert!(connected(0, 2, &[]));
+    assert!(connected(0, 3, &[]));
+    assert!(!connected(0, 2, &[1]));
+    assert!(!connected(0, 4, &[]));
+    assert!(connected(0, 4, &[3]));
+    assert_eq!(
+        algorithms::pag_possible_descendants(&pag, &[2]).unwrap(),
+        vec![0, 1, 2, 3]
+    );
+
+    // 2 is not of definite status on 0 o-> 2 o-o 3, and 1 on 0 o-o 1 o-o 2
+    // only becomes a definite non-collider without the shielding edge 0 o-> 2
+    let unshielded = graph(&[("o-o", &[(0, 1), (1, 2), (2, 3)])]);
+    let shielded = graph(&[("o-o", &[(0, 1), (1, 2), (2, 3)]), ("o->", &[(0, 2)])]);
+    assert!(algorithms::is_pag_m_connected(&unshielded, &[0], &[3], &[]).unwrap());
+    assert!(!algorithms::is_pag_m_connected(&shielded, &[0], &[3], &[]).unwrap());
+}
+
+// in MAGs every triple is of definite status, so the result has to agree with
+// the d-connection rule table for ADMGs
+#[test]
+fn test_pag_m_connected_random_mags() {
+    let num_nodes = 8;
+    let mut seed: u64 = 7;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+
+    let dconnected_table = Ruletable::dconnected_admg();
+    for _ in 0..200 {
+        let mut directed = Vec::new();
+        let mut bidirected = Vec::new();
+        for u in 0..num_nodes {
+            for v in (u + 1)..num_nodes {
+                match random(6) {
+                    0 | 1 => directed.push((u, v)),
+                    2 => bidirected.push((u, v)),
+                    _ => {}
+                }
+            }
+        }
+        let mag = graph(&[("-->", &directed), ("<->", &bidirected)]);
+        let g = Graph::new(&mag, &dconnected_table).unwrap();
+        let x = random(num_nodes as u64) as usize;
+        let z: Vec<usize> = (0..num_nodes)
+            .filter(|&v| v != x && random(4) == 0)
+            .collect();
+        let sets = HashMap::from([("X".to_owned(), vec![x]), ("Z".to_owned(), z.clone())]);
+        let sets = Sets::new(&sets, &dconnected_table).unwrap();
+        let reached = reach(&g, &sets, &dconnected_table, &Settings::default());
+        for y in 0..num_nodes {
+            assert_eq!(
+                algorithms::is_pag_m_connected(&mag, &[x], &[y], &z).unwrap(),
+                reached.contains(&y)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_parent_aid() {
+    let truth = graph(&[("-->", &[(0, 1), (1, 2), (0, 3), (3, 2)])]);
+    let guess = graph(&[("-->", &[(0, 1), (1, 2), (3, 0), (3, 2)])]);
+    assert_eq!(algorithms::parent_aid(4, &truth, &guess).unwrap(), 5);
+
+    let truth = graph(&[("-->", &[(0, 1), (2, 1), (3, 1)]), ("---", &[(2, 3)])]);
+    let guess = graph(&[
+        ("-->", &[(0, 1), (2, 1), (0, 3), (2, 3)]),
+        ("---", &[(1, 3)]),
+    ]);
+    assert_eq!(algorithms::parent_aid(4, &truth, &guess).unwrap(), 4);
+
+    let err = algorithms::parent_aid(3, &truth, &guess)
+        .expect_err("should fail on nodes outside of num_nodes");
+    assert!(err.to_string().contains("outside of the 3 nodes"), "{err}");
+}
+
+#[test]
+fn test_frontdoor() {
+    let dag = graph(&[("-->", &[(0, 1), (1, 2), (3, 0), (3, 2)])]);
+    assert_eq!(
+        algorithms::frontdoor(&dag, &[0], &[2], &[], &[1]).unwrap(),
+        Some(vec![1])
+    );
+
+    let dag = graph(&[(
+        "-->",
+        &[
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 5),
+            (4, 1),
+            (4, 9),
+            (6, 0),
+            (6, 5),
+            (7, 0),
+            (7, 3),
+            (8, 2),
+            (8, 5),
+            (9, 5),
+        ],
+    )]);
+    assert_eq!(
+        algorithms::frontdoor(&dag, &[0], &[5], &[], &[1, 2, 3, 4]).unwrap(),
+        Some(vec![1, 4])
+    );
+
+    let dag = graph(&[("-->", &[(0, 1), (1, 2), (3, 0), (3, 2), (4, 1), (4, 2)])]);
+    assert_eq!(
+        algorithms::frontdoor(&dag, &[0], &[2], &[], &[1]).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_separators() {
+    let admg = graph(&[("-->", &[(1, 2), (1, 3), (2, 3)]), ("<->", &[(0, 2)])]);
+    assert_eq!(
+        algorithms::find_nearest_dsep(&admg, &[0], &[3], &[], &[0, 1, 2, 3]).unwrap(),
+        Some(vec![1, 2])
+    );
+
+    let admg = graph(&[("-->", &[(0, 2), (1, 0), (1, 2), (2, 3), (3, 4)])]);
+    assert_eq!(
+        algorithms::find_minimal_dsep(&admg, &[0], &[4], &[], &[0, 1, 2, 3, 4]).unwrap(),
+        Some(vec![2])
+    );
+
+    let admg = graph(&[
+        ("-->", &[(1, 0), (1, 2), (2, 4), (3, 2), (3, 4)]),
+        ("<->", &[(0, 2)]),
+    ]);
+    assert_eq!(
+        algorithms::find_minimal_dsep(&admg, &[0], &[4], &[], &[0, 1, 2, 3, 4]).unwrap(),
+        Some(vec![2, 3])
+    );
+
+    // the empty set is a valid minimal separator
+    let admg = graph(&[("-->", &[(0, 1), (2, 1)])]);
+    assert_eq!(
+        algorithms::find_minimal_dsep(&admg, &[0], &[2], &[], &[1]).unwrap(),
+        Some(vec![])
+    );
+    assert_eq!(
+        algorithms::find_minimal_dsep(&admg, &[0], &[2], &[1], &[1]).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_enumerate_minimal_separators() {
+    let dag = graph(&[("-->", &[(0, 1), (0, 2), (1, 3), (2, 3)])]);
+    let separators: Vec<_> =
+        algorithms::enumerate_minimal_separators(&dag, &[0], &[3], &[], &[1, 2])
+            .unwrap()
+            .collect();
+    assert_eq!(separators, vec![vec![1, 2]]);
+
+    let admg = graph(&[
+        ("-->", &[(0, 1), (1, 4), (0, 2), (2, 3), (3, 4)]),
+        ("<->", &[(0, 5), (5, 4)]),
+    ]);
+    let mut separators: Vec<_> =
+        algorithms::enumerate_minimal_separators(&admg, &[0], &[4], &[], &[1, 2, 3, 5])
+            .unwrap()
+            .collect();
+    separators.sort();
+    assert_eq!(separators, vec![vec![1, 2], vec![1, 3]]);
+
+    // conditioning on the collider 5 opens a path that cannot be blocked
+    let mut separators =
+        algorithms::enumerate_minimal_separators(&admg, &[0], &[4], &[5], &[1, 2, 3, 5]).unwrap();
+    assert_eq!(separators.next(), None);
+}
+
+// compares against all subsets of r checked with the d-connection rule table
+#[test]
+fn test_enumerate_minimal_separators_brute_force() {
+    let (num_nodes, x, y) = (10, 3, 7);
+    let mut seed: u64 = 42;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+
+    let dconnected_table = Ruletable::dconnected_admg();
+    for _ in 0..300 {
+        let mut directed = Vec::new();
+        let mut bidirected = Vec::new();
+        for u in 0..num_nodes {
+            for v in (u + 1)..num_nodes {
+                if (u, v) == (x, y) {
+                    continue;
+                }
+                match random(8) {
+                    0 | 1 => directed.push((u, v)),
+                    2 => bidirected.push((u, v)),
+                    _ => {}
+                }
+            }
+        }
+        let admg = graph(&[("-->", &directed), ("<->", &bidirected)]);
+        let g = Graph::new(&admg, &dconnected_table).unwrap();
+        let i = if random(3) == 0 { vec![4] } else { vec![] };
+        let r: Vec<usize> = (0..num_nodes)
+            .filter(|&v| v != x && v != y && random(5) != 0)
+            .collect();
+
+        let separates = |z: &BTreeSet<usize>| {
+            let sets = HashMap::from([
+                ("X".to_owned(), vec![x]),
+                ("Z".to_owned(), z.iter().copied().collect()),
+            ]);
+            let sets = Sets::new(&sets, &dconnected_table).unwrap();
+            !reach(&g, &sets, &dconnected_table, &Settings::default()).contains(&y)
+        };
+        let free: Vec<usize> = r.iter().copied().filter(|v| !i.contains(v)).collect();
+        let mut expected = Vec::new();
+        for mask in 0..(1 << free.len()) {
+            let mut z: BTreeSet<usize> = i.iter().copied().collect();
+            z.extend(
+                (0..free.len())
+                    .filter(|k| mask & (1 << k) != 0)
+                    .map(|k| free[k]),
+            );
+            let minimal = separates(&z)
+                && z.iter().filter(|v| !i.contains(v)).all(|v| {
+                    let mut smaller = z.clone();
+                    smaller.remove(v);
+                    !separates(&smaller)
+                });
+            if minimal {
+                expected.push(z.into_iter().collect::<Vec<_>>());
+            }
+        }
+        expected.sort();
+
+        let mut found: Vec<_> = algorithms::enumerate_minimal_separators(&admg, &[x], &[y], &i, &r)
+            .unwrap()
+            .collect();
+        found.sort();
+        assert_eq!(found, expected, "graph {:?}, i {:?}, r {:?}", admg, i, r);
+    }
+}
+
+#[test]
+fn test_min_cost_separators() {
+    let dag = graph(&[("-->", &[(0, 1), (0, 2), (1, 3), (2, 3), (3, 4)])]);
+    assert_eq!(
+        algorithms::find_min_cost_dsep(&dag, &[0], &[4], &[], &[1, 2, 3], &[]).unwrap(),
+        Some((vec![3], 1.0))
+    );
+    assert_eq!(
+        algorithms::find_min_cost_dsep(&dag, &[0], &[4], &[], &[1, 2, 3], &[0.0, 1.0, 2.0, 4.0])
+            .unwrap(),
+        Some((vec![1, 2], 3.0))
+    );
+    assert_eq!(
+        algorithms::find_min_cost_dsep(&dag, &[0], &[4], &[], &[1, 2], &[0.0, f64::INFINITY])
+            .unwrap(),
+        None
+    );
+
+    // conditioning on the collider 1 requires blocking the path through 2
+    let admg = graph(&[("-->", &[(0, 1), (2, 1), (2, 3)]), ("<->", &[(0, 2)])]);
+    assert_eq!(
+        algorithms::find_min_cost_dsep(&admg, &[0], &[3], &[1], &[1, 2], &[0.0, 5.0, 0.5]).unwrap(),
+        Some((vec![1, 2], 5.5))
+    );
+
+    let error = algorithms::find_min_cost_dsep(&dag, &[0], &[4], &[], &[1, 2, 3], &[0.0, -1.0])
+        .expect_err("negative costs should be rejected");
+    assert!(error.to_string().contains("node 1 has invalid cost -1"));
+    assert!(algorithms::find_min_cost_dsep(&dag, &[0], &[4], &[], &[1], &[f64::NAN]).is_err());
+}
+
+// compares the cost against all subsets of r checked with the d-connection rule table
+#[test]
+fn test_min_cost_separators_brute_force() {
+    let (num_nodes, x, y) = (8, 2, 6);
+    let mut seed: u64 = 5;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+
+    let dconnected_table = Ruletable::dconnected_admg();
+    for _ in 0..200 {
+        let mut directed = Vec::new();
+        let mut bidirected = Vec::new();
+        for u in 0..num_nodes {
+            for v in (u + 1)..num_nodes {
+                if (u, v) == (x, y) {
+                    continue;
+                }
+                match random(7) {
+                    0 | 1 => directed.push((u, v)),
+                    2 => bidirected.push((u, v)),
+                    _ => {}
+                }
+            }
+        }
+        let admg = graph(&[("-->", &directed), ("<->", &bidirected)]);
+        let g = Graph::new(&admg, &dconnected_table).unwrap();
+        let i = if random(4) == 0 { vec![4] } else { vec![] };
+        let r: Vec<usize> = (0..num_nodes)
+            .filter(|&v| v != x && v != y && random(4) != 0)
+            .collect();
+        let costs: Vec<f64> = (0..num_nodes)
+            .map(|_| match random(6) {
+                0 => f64::INFINITY,
+                c => c as f64,
+            })
+            .collect();
+
+        let separates = |z: &[usize]| {
+            let sets = HashMap::from([("X".to_owned(), vec![x]), ("Z".to_owned(), z.to_vec())]);
+            let sets = Sets::new(&sets, &dconnected_table).unwrap();
+            !reach(&g, &sets, &dconnected_table, &Settings::default()).contains(&y)
+        };
+        let free: Vec<usize> = r
+            .iter()
+            .copied()
+            .filter(|&v| !i.contains(&v) && costs[v].is_finite())
+            .collect();
+        let mut expected = None;
+        for mask in 0..(1 << free.len()) {
+            let mut z = i.clone();
+            z.extend(
+                (0..free.len())
+                    .filter(|k| mask & (1 << k) != 0)
+                    .map(|k| free[k]),
+            );
+            let cost: f64 = z.iter().map(|&v| costs[v]).sum();
+            if separates(&z) && expected.is_none_or(|best| cost < best) {
+                expected = Some(cost);
+            }
+        }
+
+        let found = algorithms::find_min_cost_dsep(&admg, &[x], &[y], &i, &r, &costs).unwrap();
+        if let Some((z, cost)) = &found {
+            assert!(separates(z));
+            assert!(i.iter().all(|v| z.contains(v)));
+            assert!(z.iter().all(|v| i.contains(v) || free.contains(v)));
+            assert_eq!(*cost, z.iter().map(|&v| costs[v]).sum::<f64>());
+        }
+        assert_eq!(
+            found.map(|(_, cost)| cost),
+            expected,
+            "graph {:?}, i {:?}, r {:?}, costs {:?}",
+            admg,
+            i,
+            r,
+            costs
+        );
+    }
+}
+
+#[test]
+fn test_min_cost_adjustment() {
+    let cpdag = graph(&[
+        ("-->", &[(0, 2), (1, 2), (2, 5), (3, 2), (3, 5), (4, 5)]),
+        ("---", &[(0, 1), (1, 3), (1, 4), (3, 4)]),
+    ]);
+    // the minimal adjustment sets are {1, 3} and {3, 4}
+    assert_eq!(
+        algorithms::min_cost_adjustment(&cpdag, &[2], &[5], &[1.0, 1.0, 0.0, 3.0, 0.5]).unwrap(),
+        Some((vec![3, 4], 3.5))
+    );
+    assert_eq!(
+        algorithms::min_cost_adjustment(&cpdag, &[2], &[5], &[1.0, 1.0, 0.0, f64::INFINITY])
+            .unwrap(),
+        None
+    );
+    assert!(algorithms::min_cost_adjustment(&cpdag, &[2], &[5], &[1.0, f64::NAN]).is_err());
+
+    let options = algorithms::AdjustmentOptions::default();
+    let admg = graph(&[
+        ("-->", &[(0, 1), (0, 3), (1, 3), (2, 1), (2, 3), (1, 4)]),
+        ("<->", &[(0, 2)]),
+    ]);
+    let costs = [2.0, 0.0, 0.5, 0.0, 1.0];
+    let best = algorithms::enumerate_adjustment_sets(&admg, &[1], &[3], &options)
+        .unwrap()
+        .map(|z| z.iter().map(|&v| costs[v]).sum::<f64>())
+        .fold(f64::INFINITY, f64::min);
+    assert_eq!(best, 2.5);
+    assert_eq!(
+        algorithms::min_cost_adjustment(&admg, &[1], &[3], &costs).unwrap(),
+        Some((vec![0, 2], 2.5))
+    );
+}
+
+#[test]
+fn test_instruments() {
+    let admg = graph(&[
+        ("-->", &[(0, 3), (1, 0), (1, 2), (2, 0), (3, 4)]),
+        ("<->", &[(2, 4), (3, 4)]),
+    ]);
+    assert_eq!(
+        algorithms::optimal_instrument(&admg, 3, 4).unwrap(),
+        Some((vec![0], vec![1, 2]))
+    );
+    assert_eq!(
+        algorithms::sound_and_complete_instrument(&admg, 3, 4).unwrap(),
+        Some((vec![0], vec![1, 2]))
+    );
+
+    let admg = graph(&[
+        ("-->", &[(0, 1), (0, 2), (2, 3)]),
+        ("<->", &[(1, 3), (2, 3)]),
+    ]);
+    assert_eq!(algorithms::optimal_instrument(&admg, 2, 3).unwrap(), None);
+    assert_eq!(
+        algorithms::sound_and_complete_instrument(&admg, 2, 3).unwrap(),
+        Some((vec![0], vec![]))
+    );
+
+    let admg = graph(&[
+        ("-->", &[(0, 2), (2, 1), (3, 4)]),
+        ("<->", &[(1, 4), (2, 3), (3, 4)]),
+    ]);
+    assert_eq!(algorithms::optimal_instrument(&admg, 3, 4).unwrap(), None);
+    assert_eq!(
+        algorithms::sound_and_complete_instrument(&admg, 3, 4).unwrap(),
+        Some((vec![2], vec![]))
+    );
+}
+
+#[test]
+fn test_identify() {
+    use algorithms::{Hedge, Identification};
+
+    let dag = graph(&[("-->", &[(0, 1)])]);
+    let Identification::Identified(estimand) = algorithms::identify(&dag, &[0], &[1]).unwrap()
+    else {
+        panic!("effect should be identifiable");
+    };
+    assert_eq!(
+        estimand,
+        Estimand::Probability {
+            vars: vec![1],
+            given: vec![0]
+        }
+    );
+    assert_eq!(estimand.to_string(), "P(1 | 0)");
+
+    let bow = graph(&[("-->", &[(0, 1)]), ("<->", &[(0, 1)])]);
+    assert_eq!(
+        algorithms::identify(&bow, &[0], &[1]).unwrap(),
+        Identification::NotIdentifiable(Hedge {
+            f: vec![0, 1],
+            f_prime: vec![1]
+        })
+    );
+
+    let frontdoor = graph(&[("-->", &[(0, 1), (1, 2)]), ("<->", &[(0, 2)])]);
+    assert!(matches!(
+        algorithms::identify(&frontdoor, &[0], &[2]).unwrap(),
+        Identification::Identified(_)
+    ));
+
+    let confounded_mediator = graph(&[("-->", &[(0, 1), (1, 2)]), ("<->", &[(0, 1)])]);
+    assert_eq!(
+        algorithms::identify(&confounded_mediator, &[0], &[2]).unwrap(),
+        Identification::NotIdentifiable(Hedge {
+            f: vec![0, 1],
+            f_prime: vec![1]
+        })
+    );
+
+    let napkin = graph(&[
+        ("-->", &[(0, 1), (1, 2), (2, 3)]),
+        ("<->", &[(0, 2), (0, 3)]),
+    ]);
+    assert!(matches!(
+        algorithms::identify(&napkin, &[2], &[3]).unwrap(),
+        Identification::Identified(_)
+    ));
+
+    let cpdag = graph(&[("---", &[(0, 1)])]);
+    assert!(algorithms::identify(&cpdag, &[0], &[1]).is_err());
+}
+
+// probabilities of all assignments of binary variables in a DAG with the nodes
+// 0..num_nodes in topological order, the nodes in do are set to 1
+fn joint_distribution(
+    num_nodes: usize,
+    dag: &[(usize, usize)],
+    parameters: &[Vec<f64>],
+    intervention: &[usize],
+) -> Vec<f64> {
+    (0..1usize << num_nodes)
+        .map(|assignment| {
+            (0..num_nodes)
+                .map(|v| {
+                    let value = assignment >> v & 1;
+                    if intervention.contains(&v) {
+                        return value as f64;
+                    }
+                    let parent_values = dag
+                        .iter()
+                        .filter(|&&(_, w)| w == v)
+                        .enumerate()
+                        .fold(0, |index, (k, &(u, _))| index | (assignment >> u & 1) << k);
+                    let p = parameters[v][parent_values];
+                    if value == 1 {
+                        p
+                    } else {
+                        1.0 - p
+                    }
+                })
+                .product::<f64>()
+        })
+        .collect()
+}
+
+// probability that all nodes in the mask have the values of assignment
+fn marginal(joint: &[f64], mask: usize, assignment: usize) -> f64 {
+    joint
+        .iter()
+        .enumerate()
+        .filter(|&(a, _)| a & mask == assignment & mask)
+        .map(|(_, p)| p)
+        .sum()
+}
+
+fn evaluate(estimand: &Estimand, joint: &[f64], assignment: usize) -> f64 {
+    let mask = |nodes: &[usize]| nodes.iter().fold(0, |mask, v| mask | 1 << v);
+    match estimand {
+        Estimand::Probability { vars, given } => {
+            marginal(joint, mask(vars) | mask(given), assignment)
+                / marginal(joint, mask(given), assignment)
+        }
+        Estimand::Sum { over, term } => (0..1usize << over.len())
+            .map(|values| {
+                let assignment = over
+                    .iter()
+                    .enumerate()
+                    .fold(assignment & !mask(over), |assignment, (k, v)| {
+                        assignment | (values >> k & 1) << v
+                    });
+                evaluate(term, joint, assignment)
+            })
+            .sum(),
+        Estimand::Product(factors) => factors
+            .iter()
+            .map(|factor| evaluate(factor, joint, assignment))
+            .product(),
+        Estimand::Quotient(numerator, denominator) => {
+            evaluate(numerator, joint, assignment) / evaluate(denominator, joint, assignment)
+        }
+        Estimand::Covariance { .. } | Estimand::Inverse(_) => {
+            panic!("ID algorithm should not return covariances")
+        }
+    }
+}
+
+#[test]
+fn test_identify_brute_force() {
+    use algorithms::Identification;
+
+    let num_nodes = 5;
+    let mut seed: u64 = 3;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+
+    let mut identified = 0;
+    for _ in 0..200 {
+        let mut directed = Vec::new();
+        let mut bidirected = Vec::new();
+        for u in 0..num_nodes {
+            for v in (u + 1)..num_nodes {
+                match random(7) {
+                    0..=2 => directed.push((u, v)),
+                    3..=5 => bidirected.push((u, v)),
+                    _ => {}
+                }
+            }
+        }
+        let x = random(num_nodes as u64 - 1) as usize;
+        let y = num_nodes - 1;
+        let admg = graph(&[("-->", &directed), ("<->", &bidirected)]);
+
+        let estimand = match algorithms::identify(&admg, &[x], &[y]).unwrap() {
+            Identification::Identified(estimand) => estimand,
+            Identification::NotIdentifiable(hedge) => {
+                let f_prime = BTreeSet::from_iter(hedge.f_prime.iter().copied());
+                assert!(f_prime.is_subset(&BTreeSet::from_iter(hedge.f.iter().copied())));
+                assert!(hedge.f.contains(&x) && !hedge.f_prime.contains(&x));
+                continue;
+            }
+        };
+        identified += 1;
+
+        // latent nodes come first in the topological order of the latent DAG
+        let num_latents = bidirected.len();
+        let mut latent_dag: Vec<_> = directed
+            .iter()
+            .map(|&(u, v)| (u + num_latents, v + num_latents))
+            .collect();
+        for (k, &(u, v)) in bidirected.iter().enumerate() {
+            latent_dag.extend([(k, u + num_latents), (k, v + num_latents)]);
+        }
+        let parameters: Vec<Vec<f64>> = (0..num_nodes + num_latents)
+            .map(|_| {
+                (0..256)
+                    .map(|_| 0.1 + random(800) as f64 / 1000.0)
+                    .collect()
+            })
+            .collect();
+        let all_nodes = num_nodes + num_latents;
+        let observed = |joint: Vec<f64>| {
+            let mut observed = vec![0.0; 1 << num_nodes];
+            for (assignment, p) in joint.into_iter().enumerate() {
+                observed[assignment >> num_latents] += p;
+            }
+            observed
+        };
+        let joint = observed(joint_distribution(all_nodes, &latent_dag, &parameters, &[]));
+        let intervened = observed(joint_distribution(
+            all_nodes,
+            &latent_dag,
+            &parameters,
+            &[x + num_latents],
+        ));
+
+        for value in 0..2 {
+            let assignment = 1 << x | value << y;
+            let expected = marginal(&intervened, 1 << y, assignment);
+            let actual = evaluate(&estimand, &joint, assignment);
+            assert!(
+                (expected - actual).abs() < 1e-9,
+                "graph {:?}, x {}, y {}, estimand {}",
+                admg,
+                x,
+                y,
+                estimand
+            );
+        }
+    }
+    assert!(identified > 100 && identified < 200);
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeSet;
+
+use cifly::estimand::{self, Estimand};
+
+#[test]
+fn test_adjustment() {
+    let names = ["x", "z", "y"];
+    let adjustment = estimand::adjustment(&[0], &[2], &[1]);
+    assert_eq!(adjustment.to_text(&names), "sum_{z} [P(y | x, z) P(z)]");
+    assert_eq!(
+        adjustment.to_latex(&names),
+        "\\sum_{z} P(y \\mid x, z) P(z)"
+    );
+
+    let no_adjustment = estimand::adjustment(&[0], &[2], &[]);
+    assert_eq!(no_adjustment.to_text(&names), "P(y | x)");
+    assert_eq!(no_adjustment.to_string(), "P(2 | 0)");
+}
+
+#[test]
+fn test_frontdoor() {
+    let names = ["x", "m", "y"];
+    let frontdoor = estimand::frontdoor(&[0], &[2], &[1]);
+    assert_eq!(frontdoor.free_nodes(), BTreeSet::from([0, 2]));
+    assert_eq!(
+        frontdoor.to_text(&names),
+        "sum_{m} [P(m | x) sum_{x'} [P(y | x', m) P(x')]]"
+    );
+    assert_eq!(
+        frontdoor.to_latex(&names),
+        "\\sum_{m} P(m \\mid x) \\sum_{x'} P(y \\mid x', m) P(x')"
+    );
+}
+
+#[test]
+fn test_instrument() {
+    let instrument = estimand::instrument(1, 2, &[0], &[3]);
+    assert_eq!(
+        instrument.to_text(&["z", "x", "y", "w"]),
+        "(Cov(y, z | w)) / (Cov(x, z | w))"
+    );
+    assert_eq!(
+        instrument.to_latex(&["Z", "X", "Y"]),
+        "\\frac{\\operatorname{Cov}(Y, Z \\mid 3)}{\\operatorname{Cov}(X, Z \\mid 3)}"
+    );
+
+    // several instruments give the two-stage least squares estimand
+    let two_stage = estimand::instrument(1, 2, &[4, 0], &[3]);
+    assert_eq!(
+        two_stage.to_text(&["z", "x", "y", "w", "v"]),
+        "(Cov(x, z, v | w) Cov(z, v, z, v | w)^-1 Cov(z, v, y | w)) / \
+         (Cov(x, z, v | w) Cov(z, v, z, v | w)^-1 Cov(z, v, x | w))"
+    );
+    assert_eq!(
+        two_stage.free_nodes(),
+        [0, 1, 2, 3, 4].into_iter().collect()
+    );
+}
+
+#[test]
+fn test_nested_sums() {
+    let sum = Estimand::Sum {
+        over: vec![1],
+        term: Box::new(Estimand::Product(vec![
+            Estimand::Probability {
+                vars: vec![1],
+                given: vec![0],
+            },
+            Estimand::Sum {
+                over: vec![0],
+                term: Box::new(Estimand::Probability {
+                    vars: vec![0, 1],
+                    given: vec![],
+                }),
+            },
+        ])),
+    };
+    let estimand = Estimand::Product(vec![
+        sum,
+        Estimand::Probability {
+            vars: vec![0],
+            given: vec![],
+        },
+    ]);
+    assert_eq!(
+        estimand.to_latex(&["a", "b"]),
+        "\\sum_{b} \\left[P(b \\mid a) \\sum_{a'} P(a', b)\\right] P(a)"
+    );
+    assert_eq!(
+        estimand.to_text(&["a", "b"]),
+        "sum_{b} [P(b | a) sum_{a'} [P(a', b)]] P(a)"
+    );
+}
@@ -30,3 +30,1454 @@ OUTPUT ... [yield]
         vec![0, 2, 3, 4]
     );
 }
+
+#[test]
+fn test_undefined_edge() {
+    let ruletable = cifly::Ruletable::dsep();
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert("-->".to_owned(), vec![(0, 1)]);
+    edge_lists.insert("<->".to_owned(), vec![(1, 2)]);
+    let err = cifly::Graph::new(&edge_lists, &ruletable)
+        .err()
+        .expect("should fail on undefined edge");
+    assert!(
+        err.to_string().contains("edge <-> was not specified"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_multiline_rules() {
+    let ruletable_str = "
+EDGES --> <--, ---
+SETS X
+COLORS init, yield
+START ... [init] AT X # start with all edge types
+OUTPUT ... [yield]
+
+... [init]  | ---      [yield] | next not in X
+... [yield] | ---, \\
+              -->      [yield] | (
+                  # nodes in X are never revisited
+                  next not in X
+              )";
+    let ruletable =
+        cifly::Ruletable::from_multiline_string(ruletable_str).expect("should parse ruletable");
+
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert("-->".to_owned(), vec![(2, 1), (2, 3), (3, 4), (5, 4)]);
+    edge_lists.insert("---".to_owned(), vec![(0, 1), (0, 2)]);
+    let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![1]);
+    let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+    let settings = cifly::Settings::new(false, false);
+
+    assert_eq!(
+        cifly::reach::reach(&graph, &sets, &ruletable, &settings),
+        vec![0, 2, 3, 4]
+    );
+}
+
+#[test]
+fn test_multiline_error_location() {
+    let ruletable_str = "EDGES --> <--
+SETS X, Z
+START <-- AT X
+OUTPUT ...
+
+--> | <-- | (current in Z
+             and current in Y)";
+    let err = cifly::Ruletable::from_multiline_string(ruletable_str)
+        .err()
+        .expect("should fail on undefined set");
+    assert!(err.to_string().contains("lines 6-7"));
+
+    let err = cifly::Ruletable::from_multiline_string("EDGES --> <--\nSETS X, \\")
+        .err()
+        .expect("should fail on dangling line continuation");
+    assert!(err.to_string().contains("line 2"));
+}
+
+#[test]
+fn test_definitions() {
+    let ruletable_str = "
+EDGES --> <--
+DEFINE blocked = current in W
+DEFINE fresh = next not in X
+SETS X, W
+COLORS init, yield
+START ... [init] AT X
+OUTPUT ... [yield]
+
+... [init]  | <-- [yield] | fresh
+--> [yield] | <-- [yield] | blocked
+... [yield] | ... [yield] | fresh and not blocked";
+    let ruletable =
+        cifly::Ruletable::from_multiline_string(ruletable_str).expect("should parse ruletable");
+
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert("-->".to_owned(), vec![(1, 0), (1, 2), (3, 2), (2, 4)]);
+    let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![0]);
+    sets.insert("W".to_owned(), vec![4]);
+    let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+    let settings = cifly::Settings::new(false, false);
+
+    let mut reached = cifly::reach::reach(&graph, &sets, &ruletable, &settings);
+    reached.sort();
+    assert_eq!(reached, vec![1, 2, 3, 4]);
+
+    // definitions may be stated below the rules using them
+    let reordered = cifly::Ruletable::from_multiline_string(
+        "
+EDGES --> <--
+SETS X, W
+COLORS init, yield
+START ... [init] AT X
+OUTPUT ... [yield]
+
+... [init]  | <-- [yield] | fresh
+--> [yield] | <-- [yield] | blocked
+... [yield] | ... [yield] | fresh and not blocked
+
+DEFINE blocked = current in W
+DEFINE fresh = next not in X",
+    )
+    .expect("should parse ruletable with definitions after rules");
+    let mut reached = cifly::reach::reach(&graph, &sets, &reordered, &settings);
+    reached.sort();
+    assert_eq!(reached, vec![1, 2, 3, 4]);
+
+    let header = "EDGES --> <--\nSETS X\nSTART <-- AT X\nOUTPUT ...\n";
+    for (definitions, expected) in [
+        ("DEFINE a = b\nDEFINE b = a", "cyclic definition"),
+        (
+            "DEFINE a = next in Y",
+            "could not find set or definition 'Y'",
+        ),
+        ("DEFINE X = true", "already declared as a set"),
+        ("DEFINE not = true", "reserved keyword"),
+        ("DEFINE a = true\nDEFINE a = false", "twice"),
+    ] {
+        let err = cifly::Ruletable::from_multiline_string(&format!("{header}{definitions}"))
+            .err()
+            .expect("should fail on invalid definition");
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
+
+#[test]
+fn test_set_names() {
+    let ruletable_str = "
+EDGES --> <--
+DEFINE blocked = current in W
+DEFINE unused = next in V
+SETS X, V, W, Y
+COLORS init, yield
+START ... [init] AT X
+START --> [init] AT Y
+OUTPUT ... [yield]
+
+... [init]  | <-- [yield] | next not in Y
+... [yield] | ... [yield] | not blocked";
+    let ruletable =
+        cifly::Ruletable::from_multiline_string(ruletable_str).expect("should parse ruletable");
+    assert_eq!(ruletable.set_names(), vec!["X", "V", "W", "Y"]);
+    assert_eq!(ruletable.start_set_names(), vec!["X", "Y"]);
+    assert_eq!(ruletable.expression_set_names(), vec!["W", "Y"]);
+
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![0]);
+    sets.insert("W".to_owned(), vec![]);
+    assert!(cifly::Sets::new(&sets, &ruletable).is_ok());
+    let err = cifly::Sets::new_strict(&sets, &ruletable)
+        .err()
+        .expect("should fail on missing sets");
+    assert!(err.to_string().contains("not specified: V, Y"), "{err}");
+
+    sets.insert("V".to_owned(), vec![]);
+    sets.insert("Y".to_owned(), vec![1]);
+    assert!(cifly::Sets::new_strict(&sets, &ruletable).is_ok());
+    sets.insert("Z".to_owned(), vec![]);
+    assert!(cifly::Sets::new_strict(&sets, &ruletable).is_err());
+}
+
+#[test]
+fn test_introspection() {
+    use cifly::expression::{Expression, Op, RuletableAtom};
+    use cifly::ruletable::{OutputSpec, StartSpec};
+
+    let ruletable_str = "
+EDGES --> <--, ---
+SETS X, Z
+COLORS init, yield
+START <-- [init] AT X
+OUTPUT ... [yield]
+
+--> [init]  | ---, <-- [...] | next not in Z and (current in X or not next in X)
+... [yield] | ...            | true";
+    let ruletable =
+        cifly::Ruletable::from_multiline_string(ruletable_str).expect("should parse ruletable");
+    assert_eq!(ruletable.edge_names(), vec!["-->", "<--", "---"]);
+    assert_eq!(ruletable.reverse_edge_name("-->").as_deref(), Some("<--"));
+    assert_eq!(ruletable.reverse_edge_name("---").as_deref(), Some("---"));
+    assert_eq!(ruletable.reverse_edge_name("o->"), None);
+    assert_eq!(ruletable.color_names(), vec!["init", "yield"]);
+    assert_eq!(
+        ruletable.start_specs(),
+        vec![StartSpec {
+            set: "X".to_owned(),
+            edge: "<--".to_owned(),
+            color: Some("init".to_owned()),
+        }]
+    );
+    let outputs = ruletable.output_specs();
+    assert_eq!(outputs.len(), 3);
+    assert_eq!(
+        outputs[2],
+        OutputSpec {
+            edge: "---".to_owned(),
+            color: Some("yield".to_owned()),
+        }
+    );
+
+    let rules = ruletable.rule_specs();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].prev_edges, vec!["-->"]);
+    assert_eq!(rules[0].prev_colors, vec!["init"]);
+    assert_eq!(rules[0].next_edges, vec!["---", "<--"]);
+    assert_eq!(rules[0].next_colors, vec!["init", "yield"]);
+    let set_names = ruletable.set_names();
+    assert_eq!(
+        rules[0].expression.to_ruletable_string(&set_names),
+        "next not in Z and (current in X or not next in X)"
+    );
+    assert_eq!(rules[1].prev_edges, vec!["-->", "<--", "---"]);
+    assert_eq!(rules[1].expression, Expression::Atom(RuletableAtom::True));
+    let Expression::Junction(Op::And, operands) = &rules[0].expression else {
+        panic!("expected a conjunction");
+    };
+    assert_eq!(
+        operands[0],
+        Expression::Junction(
+            Op::NotIn,
+            vec![
+                Expression::Atom(RuletableAtom::Next),
+                Expression::Atom(RuletableAtom::Set(1))
+            ]
+        )
+    );
+
+    // rendering the rules of the bundled tables and parsing them again gives the same rules
+    for name in cifly::tables::NAMES {
+        let ruletable = cifly::Ruletable::bundled(name).expect("bundled rule table should exist");
+        let edges = ruletable.edge_names();
+        let mut declared = Vec::new();
+        for (i, edge) in edges.iter().enumerate() {
+            let reverse = ruletable
+                .reverse_edge_name(edge)
+                .expect("edge should exist");
+            if reverse == *edge {
+                declared.push(edge.clone());
+            } else if edges[..i].iter().all(|e| *e != reverse) {
+                declared.push(format!("{edge} {reverse}"));
+            }
+        }
+        let colors = ruletable.color_names();
+        let color = |names: &[String]| {
+            if colors.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", names.join(", "))
+            }
+        };
+        let mut source = format!(
+            "EDGES {}\nSETS {}\n",
+            declared.join(", "),
+            ruletable.set_names().join(", ")
+        );
+        if !colors.is_empty() {
+            source.push_str(&format!("COLORS {}\n", colors.join(", ")));
+        }
+        let start = &ruletable.start_specs()[0];
+        source.push_str(&format!(
+            "START {}{} AT {}\nOUTPUT ...\n",
+            start.edge,
+            color(&start.color.iter().cloned().collect::<Vec<_>>()),
+            start.set
+        ));
+        for rule in ruletable.rule_specs() {
+            source.push_str(&format!(
+                "{}{} | {}{} | {}\n",
+                rule.prev_edges.join(", "),
+                color(&rule.prev_colors),
+                rule.next_edges.join(", "),
+                color(&rule.next_colors),
+                rule.expression.to_ruletable_string(&ruletable.set_names())
+            ));
+        }
+        let reparsed = cifly::Ruletable::from_multiline_string(&source)
+            .unwrap_or_else(|e| panic!("rendered table {name} should parse: {e}\n{source}"));
+        assert_eq!(reparsed.edge_names(), edges, "{name}");
+        assert_eq!(reparsed.rule_specs(), ruletable.rule_specs(), "{name}");
+    }
+}
+
+#[test]
+fn test_check_equivalence() {
+    use cifly::equivalence::{check_equivalence, Difference};
+
+    let dsep = cifly::Ruletable::dsep();
+    assert_eq!(check_equivalence(&dsep, &dsep).unwrap(), None);
+
+    // reordered declarations and rules with a split catch-all rule
+    let refactored = cifly::Ruletable::from_multiline_string(
+        "
+EDGES <-- -->
+SETS Z, X
+START <-- AT X
+OUTPUT <--, -->
+
+<-- | ... | not current in Z
+--> | --> | current not in Z
+--> | <-- | current in Z",
+    )
+    .expect("should parse ruletable");
+    assert_eq!(check_equivalence(&dsep, &refactored).unwrap(), None);
+    assert_eq!(check_equivalence(&refactored, &dsep).unwrap(), None);
+
+    let wrong = cifly::Ruletable::from_multiline_string(
+        "
+EDGES --> <--
+SETS X, Z
+START <-- AT X
+OUTPUT ...
+
+--> | <-- | next in Z
+... | ... | current not in Z",
+    )
+    .expect("should parse ruletable");
+    let difference = check_equivalence(&dsep, &wrong).unwrap();
+    assert_eq!(
+        difference,
+        Some(Difference::Transition {
+            prev_edge: "-->".to_owned(),
+            prev_color: None,
+            next_edge: "<--".to_owned(),
+            next_color: None,
+            current_sets: vec!["Z".to_owned()],
+            next_sets: vec![],
+            allowed_by_first: true,
+        })
+    );
+    assert_eq!(
+        difference.unwrap().to_string(),
+        "transition --> | <-- with current in {Z} and next in {} only allowed by first rule table"
+    );
+
+    let colored = "
+EDGES --> <--
+SETS X, Z
+COLORS init, yield
+START <-- [init] AT X
+OUTPUT ... [yield]
+
+... [init]  | --> [yield] | current not in Z
+... [init]  | <-- [init]  | current not in Z
+... [yield] | ... [yield] | next not in X";
+    let colored_table =
+        cifly::Ruletable::from_multiline_string(colored).expect("should parse ruletable");
+    let renamed = cifly::Ruletable::from_multiline_string(
+        &colored.replace("init", "first").replace("yield", "second"),
+    )
+    .expect("should parse ruletable");
+    assert_eq!(check_equivalence(&colored_table, &renamed).unwrap(), None);
+    // swapped names and a different declaration order need a color bijection
+    let swapped = cifly::Ruletable::from_multiline_string(
+        &colored
+            .replace("init", "tmp")
+            .replace("yield", "init")
+            .replace("tmp", "yield")
+            .replace("COLORS yield, init", "COLORS init, yield"),
+    )
+    .expect("should parse ruletable");
+    assert_eq!(check_equivalence(&colored_table, &swapped).unwrap(), None);
+    assert_eq!(check_equivalence(&swapped, &colored_table).unwrap(), None);
+    let restarted = cifly::Ruletable::from_multiline_string(
+        &colored.replace("START <-- [init]", "START ... [init]"),
+    )
+    .expect("should parse ruletable");
+    assert_eq!(
+        check_equivalence(&colored_table, &restarted)
+            .unwrap()
+            .map(|d| d.to_string()),
+        Some("start state --> [init] at X only in second rule table".to_owned())
+    );
+
+    assert!(check_equivalence(&dsep, &colored_table).is_err());
+    let many_sets: Vec<_> = (0..11).map(|i| format!("S{i}")).collect();
+    let many = cifly::Ruletable::from_multiline_string(&format!(
+        "EDGES --> <--\nSETS {}\nSTART --> AT S0\nOUTPUT ...\n... | ... | current in {}",
+        many_sets.join(", "),
+        many_sets.join(" or current in ")
+    ))
+    .expect("should parse ruletable");
+    let error = check_equivalence(&many, &many).expect_err("11 sets should be too many");
+    assert!(error.to_string().contains("depends on 11 sets"));
+    assert!(check_equivalence(&dsep, &cifly::Ruletable::ancestors_admg()).is_err());
+
+    // equivalent tables give the same result as reach on random graphs, and a
+    // difference in a transition shows up as a different result on some graph
+    let mut seed = 7_u64;
+    let mut rand = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+    let settings = cifly::Settings::new(false, false);
+    let mut found_difference = false;
+    for _ in 0..200 {
+        let n = 6;
+        let mut edges = Vec::new();
+        for u in 0..n {
+            for v in (u + 1)..n {
+                if rand(3) == 0 {
+                    edges.push((u, v));
+                }
+            }
+        }
+        let mut edge_lists = HashMap::new();
+        edge_lists.insert("-->".to_owned(), edges);
+        let mut sets = HashMap::new();
+        sets.insert("X".to_owned(), vec![rand(n as u64) as usize]);
+        sets.insert(
+            "Z".to_owned(),
+            (0..n).filter(|_| rand(3) == 0).collect::<Vec<_>>(),
+        );
+        let graph = cifly::Graph::new(&edge_lists, &dsep).expect("should parse graph");
+        let instance_sets = cifly::Sets::new(&sets, &dsep).expect("should parse sets");
+        let mut expected = cifly::reach::reach(&graph, &instance_sets, &dsep, &settings);
+        expected.sort();
+        for (table, equivalent) in [(&refactored, true), (&wrong, false)] {
+            let graph = cifly::Graph::new(&edge_lists, table).expect("should parse graph");
+            let instance_sets = cifly::Sets::new(&sets, table).expect("should parse sets");
+            let mut result = cifly::reach::reach(&graph, &instance_sets, table, &settings);
+            result.sort();
+            if equivalent {
+                assert_eq!(result, expected);
+            } else {
+                found_difference |= result != expected;
+            }
+        }
+    }
+    assert!(found_difference);
+}
+
+#[test]
+fn test_minimize() {
+    use cifly::minimize::{minimize, MinimizationReport};
+
+    let redundant = cifly::Ruletable::from_multiline_string(
+        "
+EDGES --> <--
+SETS X, Z, W
+COLORS init, yield, again, dead
+START <-- [init] AT X
+OUTPUT ... [yield, again]
+
+... [init]         | ... [yield] | current not in Z or current not in Z
+... [yield, again] | --> [yield] | true and next not in Z
+... [yield, again] | <-- [again] | next not in Z
+... [yield, again] | ... [dead]  | false
+... [dead]         | ... [init]  | true
+--> [yield]        | --> [yield] | current in W",
+    )
+    .expect("should parse ruletable");
+    let minimized = minimize(&redundant).expect("should minimize ruletable");
+    assert_eq!(
+        minimized.report,
+        MinimizationReport {
+            unreachable_colors: vec!["dead".to_owned()],
+            merged_colors: vec![vec!["yield".to_owned(), "again".to_owned()]],
+            removed_rules: vec![3, 4, 5],
+            simplified_rules: vec![0, 1],
+            added_rules: 0,
+        }
+    );
+    assert_eq!(
+        minimized.source,
+        "EDGES --> <--
+SETS X, Z, W
+COLORS init, yield
+START <-- [init] AT X
+OUTPUT ... [yield]
+
+... [init] | ... [yield] | current not in Z
+... [yield] | --> [yield] | next not in Z
+... [yield] | <-- [yield] | next not in Z
+"
+    );
+
+    // rules distinguishing merged colors are replaced by rules for the merged color
+    let split = cifly::Ruletable::from_multiline_string(
+        "
+EDGES --> <--
+SETS X, Z
+COLORS init, in, out
+START --> [init] AT X
+OUTPUT ... [in, out]
+
+--> [init] | --> [in]  | next in Z
+--> [init] | --> [out] | next not in Z
+... [in, out] | ... [in, out] | current not in X",
+    )
+    .expect("should parse ruletable");
+    let minimized = minimize(&split).expect("should minimize ruletable");
+    assert_eq!(minimized.report.merged_colors, vec![vec!["in", "out"]]);
+    assert_eq!(minimized.report.added_rules, 1);
+    assert_eq!(minimized.report.removed_rules, vec![0, 1]);
+    assert!(minimized.source.contains("--> [init] | --> [in] | true\n"));
+
+    let many_sets: Vec<_> = (0..11).map(|i| format!("S{i}")).collect();
+    let many = cifly::Ruletable::from_multiline_string(&format!(
+        "EDGES --> <--\nSETS {}\nSTART --> AT S0\nOUTPUT ...\n... | ... | current in {}",
+        many_sets.join(", "),
+        many_sets.join(" or current in ")
+    ))
+    .expect("should parse ruletable");
+    let error = minimize(&many).err().expect("11 sets should be too many");
+    assert!(error.to_string().contains("rules refer to 11 sets"));
+
+    // minimizing keeps the result of reach on random graphs
+    let mut seed = 11_u64;
+    let mut rand = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+    let settings = cifly::Settings::new(false, false);
+    let mut tables: Vec<_> = cifly::tables::NAMES
+        .iter()
+        .map(|name| cifly::Ruletable::bundled(name).expect("bundled rule table should exist"))
+        .collect();
+    tables.push(redundant);
+    tables.push(split);
+    for ruletable in tables.iter() {
+        let minimized = minimize(ruletable).expect("should minimize ruletable");
+        assert_eq!(minimized.ruletable.edge_names(), ruletable.edge_names());
+        assert_eq!(minimized.ruletable.set_names(), ruletable.set_names());
+        assert!(minimized.ruletable.rule_specs().len() <= ruletable.rule_specs().len() + 1);
+        let edge_names = ruletable.edge_names();
+        for _ in 0..30 {
+            let n = 7;
+            let mut edge_lists: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+            for u in 0..n {
+                for v in (u + 1)..n {
+                    if rand(3) == 0 {
+                        let edge = &edge_names[rand(edge_names.len() as u64) as usize];
+                        edge_lists.entry(edge.clone()).or_default().push((u, v));
+                    }
+                }
+            }
+            let mut sets = HashMap::new();
+            for set in ruletable.set_names() {
+                let elements: Vec<_> = (0..n).filter(|_| rand(3) == 0).collect();
+                sets.insert(set, elements);
+            }
+            let run = |table: &cifly::Ruletable| {
+                let graph = cifly::Graph::new(&edge_lists, table).expect("should parse graph");
+                let instance_sets = cifly::Sets::new(&sets, table).expect("should parse sets");
+                let mut result = cifly::reach::reach(&graph, &instance_sets, table, &settings);
+                result.sort();
+                result
+            };
+            assert_eq!(
+                run(&minimized.ruletable),
+                run(ruletable),
+                "{}",
+                minimized.source
+            );
+        }
+    }
+}
+
+#[test]
+fn test_include() {
+    let ruletable = cifly::Ruletable::from_file("tests/ruletables/possible_ancestors_cpdag.txt")
+        .expect("should parse ruletable");
+
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert("-->".to_owned(), vec![(0, 1), (2, 1), (1, 3)]);
+    edge_lists.insert("---".to_owned(), vec![(3, 4)]);
+    let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![3]);
+    sets.insert("W".to_owned(), vec![2]);
+    let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+    let settings = cifly::Settings::new(false, false);
+
+    let mut reached = cifly::reach::reach(&graph, &sets, &ruletable, &settings);
+    reached.sort();
+    assert_eq!(reached, vec![0, 1, 3, 4]);
+
+    let err = cifly::Ruletable::from_file("tests/ruletables/cyclic_include.txt")
+        .err()
+        .expect("should fail on cyclic include");
+    assert!(err.to_string().contains("cyclic include"));
+}
+
+#[test]
+fn test_bundled_tables() {
+    for name in cifly::tables::NAMES {
+        assert!(cifly::Ruletable::bundled(name).is_some());
+    }
+    assert!(cifly::Ruletable::bundled("unknown").is_none());
+
+    let ruletable = cifly::Ruletable::dsep();
+
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert("-->".to_owned(), vec![(0, 1), (2, 1), (1, 3)]);
+    let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![0]);
+    sets.insert("Z".to_owned(), vec![3]);
+    let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+    let settings = cifly::Settings::new(false, false);
+
+    let mut reached = cifly::reach::reach(&graph, &sets, &ruletable, &settings);
+    reached.sort();
+    assert_eq!(reached, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn test_reach_all_pairs() {
+    let mut seed: u64 = 5;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+    let settings = cifly::Settings::default();
+    // the last table refers to X in its rules, so reach is run for every node
+    let not_in_x_table = "EDGES --> <--, <->
+SETS X, Z
+START <-- AT X
+OUTPUT ...
+
+... | <--, <-> | next not in X and current not in Z";
+    let tables = [
+        (cifly::Ruletable::ancestors_admg(), vec![]),
+        (cifly::Ruletable::descendants_admg(), vec![]),
+        (cifly::Ruletable::dconnected_admg(), vec!["Z"]),
+        (cifly::Ruletable::closure_admg(), vec!["Z", "A"]),
+        (
+            cifly::Ruletable::from_multiline_string(not_in_x_table)
+                .expect("should parse ruletable"),
+            vec!["Z"],
+        ),
+    ];
+
+    for round in 0..50 {
+        // directed cycles are allowed to exercise the condensation
+        let mut edge_lists = HashMap::new();
+        let mut directed = Vec::new();
+        let mut bidirected = Vec::new();
+        for u in 0..8 {
+            for v in 0..8 {
+                match random(10) {
+                    0 | 1 if u != v => directed.push((u, v)),
+                    2 if u < v => bidirected.push((u, v)),
+                    _ => {}
+                }
+            }
+        }
+        edge_lists.insert("-->".to_owned(), directed);
+        edge_lists.insert("<->".to_owned(), bidirected);
+        let z: Vec<_> = (0..10).filter(|_| random(3) == 0).collect();
+
+        for (ruletable, set_names) in tables.iter() {
+            let graph = cifly::Graph::new(&edge_lists, ruletable).expect("should parse graph");
+            let mut sets: HashMap<_, _> = set_names
+                .iter()
+                .map(|&name| (name.to_owned(), z.clone()))
+                .collect();
+            let all_pairs = cifly::reach::reach_all_pairs(
+                &graph,
+                &cifly::Sets::new(&sets, ruletable).expect("should parse sets"),
+                ruletable,
+                "X",
+            )
+            .expect("should compute all pairs");
+
+            for v in 0..all_pairs.num_rows() {
+                sets.insert("X".to_owned(), vec![v]);
+                let sets = cifly::Sets::new(&sets, ruletable).expect("should parse sets");
+                let mut expected = cifly::reach::reach(&graph, &sets, ruletable, &settings);
+                expected.sort();
+                assert_eq!(
+                    all_pairs.row(v).collect::<Vec<_>>(),
+                    expected,
+                    "round {round}, node {v}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_reach_any() {
+    let mut seed: u64 = 11;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+    let ruletable = cifly::Ruletable::dconnected_admg();
+    let settings = cifly::Settings::default();
+    let mut found = 0;
+
+    for round in 0..100 {
+        let mut edge_lists = HashMap::new();
+        let mut directed = Vec::new();
+        let mut bidirected = Vec::new();
+        for u in 0..8 {
+            for v in (u + 1)..8 {
+                match random(6) {
+                    0 => directed.push((u, v)),
+                    1 => bidirected.push((u, v)),
+                    _ => {}
+                }
+            }
+        }
+        let adjacent = |u: usize, v: usize| {
+            directed.contains(&(u, v))
+                || directed.contains(&(v, u))
+                || bidirected.contains(&(u.min(v), u.max(v)))
+        };
+        edge_lists.insert("-->".to_owned(), directed.clone());
+        edge_lists.insert("<->".to_owned(), bidirected.clone());
+        let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+
+        let x = vec![random(8) as usize];
+        let z: Vec<_> = (0..8)
+            .filter(|v| !x.contains(v) && random(3) == 0)
+            .collect();
+        let targets: Vec<_> = (0..8).filter(|_| random(3) == 0).collect();
+        let mut sets = HashMap::new();
+        sets.insert("X".to_owned(), x.clone());
+        sets.insert("Z".to_owned(), z);
+        let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+        let reached = cifly::reach::reach(&graph, &sets, &ruletable, &settings);
+        for v in 0..8 {
+            assert_eq!(
+                cifly::reach::reaches(&graph, &sets, &ruletable, v),
+                reached.contains(&v)
+            );
+        }
+        match cifly::reach::reach_any(&graph, &sets, &ruletable, &targets) {
+            Some(walk) => {
+                found += 1;
+                assert!(x.contains(&walk[0]), "round {round}, walk {walk:?}");
+                assert!(targets.contains(walk.last().unwrap()));
+                assert!(reached.contains(walk.last().unwrap()));
+                assert!(walk.windows(2).all(|w| adjacent(w[0], w[1])));
+            }
+            None => assert!(targets.iter().all(|v| !reached.contains(v))),
+        }
+    }
+    assert!(found > 20 && found < 100);
+}
+
+// every map has its own random iteration order, which may not affect the result
+#[test]
+fn test_reach_deterministic() {
+    let ruletable = cifly::Ruletable::dconnected_admg();
+    let edges = [
+        ("<->", vec![(0, 2), (2, 5)]),
+        ("-->", vec![(0, 1), (3, 2), (1, 4)]),
+        ("<--", vec![(0, 3)]),
+    ];
+    let settings = cifly::Settings::default();
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![0]);
+    sets.insert("Z".to_owned(), vec![2]);
+    let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+    for round in 0..20 {
+        let mut edge_lists = HashMap::new();
+        for k in 0..edges.len() {
+            let (edge, list) = &edges[(k + round) % edges.len()];
+            edge_lists.insert(edge.to_string(), list.clone());
+        }
+        let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+        // the neighbors of 0 are ordered by the edge types -->, <-- and <->
+        assert_eq!(
+            cifly::reach::reach(&graph, &sets, &ruletable, &settings),
+            vec![0, 1, 3, 2, 4, 5]
+        );
+        assert_eq!(
+            cifly::reach::reach_sorted(&graph, &sets, &ruletable, &settings),
+            vec![0, 1, 2, 3, 4, 5]
+        );
+        let bitset = cifly::reach::reach_bitset(&graph, &sets, &ruletable, &settings);
+        assert_eq!(bitset.size(), 6);
+        assert_eq!(bitset.count(), 6);
+        assert!(bitset.contains(3) && !bitset.contains(6));
+    }
+
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![1]);
+    sets.insert("Z".to_owned(), vec![0, 2]);
+    let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+    let edge_lists: HashMap<_, _> = edges
+        .iter()
+        .map(|(edge, list)| (edge.to_string(), list.clone()))
+        .collect();
+    let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+    let bitset = cifly::reach::reach_bitset(&graph, &sets, &ruletable, &settings);
+    assert_eq!(
+        bitset.iter().collect::<Vec<_>>(),
+        cifly::reach::reach_sorted(&graph, &sets, &ruletable, &settings)
+    );
+}
+
+#[test]
+fn test_reach_distances() {
+    let ruletable = cifly::Ruletable::dconnected_admg();
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert(
+        "-->".to_owned(),
+        vec![(0, 1), (2, 1), (1, 3), (2, 4), (4, 3)],
+    );
+    edge_lists.insert("<->".to_owned(), vec![(0, 5)]);
+    let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![0]);
+    sets.insert("Z".to_owned(), vec![3]);
+    let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+    let mut settings = cifly::Settings::default();
+    let mut distances = cifly::reach::reach_distances(&graph, &sets, &ruletable, &settings);
+    distances.sort();
+    assert_eq!(
+        distances,
+        vec![(0, 0), (1, 1), (2, 4), (3, 2), (4, 3), (5, 1)]
+    );
+
+    for (v, d) in distances {
+        settings.max_depth = Some(d);
+        assert!(cifly::reach::reach(&graph, &sets, &ruletable, &settings).contains(&v));
+        if d > 0 {
+            settings.max_depth = Some(d - 1);
+            assert!(!cifly::reach::reach(&graph, &sets, &ruletable, &settings).contains(&v));
+        }
+    }
+}
+
+#[test]
+fn test_reach_weighted() {
+    let ruletable = cifly::Ruletable::dconnected_admg();
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert(
+        "-->".to_owned(),
+        vec![
+            (0, 1, 5.0),
+            (0, 2, 1.0),
+            (2, 1, 1.0),
+            (1, 3, 0.5),
+            (4, 3, 2.0),
+        ],
+    );
+    edge_lists.insert("<->".to_owned(), vec![(0, 4, 4.0)]);
+    let mut graph =
+        cifly::Graph::new_weighted(&edge_lists, &ruletable).expect("should parse graph");
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![0]);
+    sets.insert("Z".to_owned(), vec![1]);
+    let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+    // the walk 0 --> 2 --> 1 <-- 2 is cheaper than the edge 0 --> 1
+    assert_eq!(
+        cifly::reach::reach_weighted(&graph, &sets, &ruletable),
+        vec![(0, 0.0), (2, 1.0), (1, 2.0), (4, 4.0), (3, 6.0)]
+    );
+
+    graph
+        .set_node_weights(&[1.0, 0.0, 10.0])
+        .expect("should set node weights");
+    assert_eq!(
+        cifly::reach::reach_weighted(&graph, &sets, &ruletable),
+        vec![(0, 1.0), (4, 5.0), (1, 6.0), (3, 7.0), (2, 12.0)]
+    );
+    assert!(graph.set_node_weights(&[1.0, -1.0]).is_err());
+    edge_lists.insert("<->".to_owned(), vec![(0, 4, f64::NAN)]);
+    assert!(cifly::Graph::new_weighted(&edge_lists, &ruletable).is_err());
+}
+
+#[test]
+fn test_reach_weighted_unit_weights() {
+    let mut seed: u64 = 29;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+    let ruletable = cifly::Ruletable::dconnected_admg();
+
+    for _ in 0..50 {
+        let mut edge_lists = HashMap::new();
+        for edge in ["-->", "<->"] {
+            let edges: Vec<_> = (0..8)
+                .flat_map(|u| ((u + 1)..8).map(move |v| (u, v, 1.0)))
+                .filter(|_| random(5) == 0)
+                .collect();
+            edge_lists.insert(edge.to_owned(), edges);
+        }
+        let graph =
+            cifly::Graph::new_weighted(&edge_lists, &ruletable).expect("should parse graph");
+        let mut sets = HashMap::new();
+        sets.insert("X".to_owned(), vec![random(8) as usize]);
+        let z: Vec<_> = (0..8).filter(|_| random(4) == 0).collect();
+        sets.insert("Z".to_owned(), z);
+        let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+        let mut weighted: Vec<_> = cifly::reach::reach_weighted(&graph, &sets, &ruletable)
+            .into_iter()
+            .map(|(v, cost)| (v, cost as usize))
+            .collect();
+        assert!(weighted.windows(2).all(|w| w[0].1 <= w[1].1));
+        weighted.sort();
+        let mut distances =
+            cifly::reach::reach_distances(&graph, &sets, &ruletable, &Default::default());
+        distances.sort();
+        assert_eq!(weighted, distances);
+    }
+}
+
+// compares against reach with every node as the only element of X
+#[test]
+fn test_reach_reverse() {
+    let mut seed: u64 = 31;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+    let tables = [
+        cifly::Ruletable::dconnected_admg(),
+        cifly::Ruletable::from_multiline_string(
+            "
+EDGES --> <--, <->
+SETS X, Z
+START <-- AT X
+OUTPUT ...
+
+... | <-- | next not in Z",
+        )
+        .expect("should parse ruletable"),
+        // refers to X, so the backward search does not apply
+        cifly::Ruletable::from_multiline_string(
+            "
+EDGES --> <--, <->
+SETS X, Z
+START --> AT X
+OUTPUT ...
+
+... | --> | next not in X and current not in Z",
+        )
+        .expect("should parse ruletable"),
+        // start states from Z do not depend on X
+        cifly::Ruletable::from_multiline_string(
+            "
+EDGES --> <--, <->
+SETS X, Z
+COLORS a, b
+START --> [a] AT X
+START <-> [a] AT Z
+OUTPUT --> [b]
+
+... [a] | -->, <-> [b] | true
+... [b] | --> [a]      | true",
+        )
+        .expect("should parse ruletable"),
+    ];
+    let settings = cifly::Settings::default();
+
+    for round in 0..120 {
+        let ruletable = &tables[round % tables.len()];
+        let mut edge_lists = HashMap::new();
+        for edge in ["-->", "<->"] {
+            let edges: Vec<_> = (0..7)
+                .flat_map(|u| ((u + 1)..7).map(move |v| (u, v)))
+                .filter(|_| random(5) == 0)
+                .collect();
+            edge_lists.insert(edge.to_owned(), edges);
+        }
+        let graph = cifly::Graph::new(&edge_lists, ruletable).expect("should parse graph");
+        let z: Vec<_> = (0..9).filter(|_| random(4) == 0).collect();
+        let targets: Vec<_> = (0..9).filter(|_| random(4) == 0).collect();
+        let sets = HashMap::from([("X".to_owned(), vec![]), ("Z".to_owned(), z.clone())]);
+        let sets = cifly::Sets::new(&sets, ruletable).expect("should parse sets");
+
+        let n = edge_lists
+            .values()
+            .flatten()
+            .flat_map(|&(u, v)| [u, v])
+            .chain(z.iter().copied())
+            .max()
+            .map_or(0, |v| v + 1);
+        let expected: Vec<_> = (0..n)
+            .filter(|&v| {
+                let sets = HashMap::from([("X".to_owned(), vec![v]), ("Z".to_owned(), z.clone())]);
+                let sets = cifly::Sets::new(&sets, ruletable).expect("should parse sets");
+                cifly::reach::reach(&graph, &sets, ruletable, &settings)
+                    .iter()
+                    .any(|u| targets.contains(u))
+            })
+            .collect();
+        assert_eq!(
+            cifly::reach::reach_reverse(&graph, &sets, ruletable, "X", &targets)
+                .expect("X should be declared"),
+            expected,
+            "round {round}, graph {edge_lists:?}, z {z:?}, targets {targets:?}"
+        );
+    }
+
+    let ruletable = cifly::Ruletable::ancestors_admg();
+    let graph = cifly::Graph::new(&HashMap::new(), &ruletable).expect("should parse graph");
+    let sets = cifly::Sets::new(&HashMap::new(), &ruletable).expect("should parse sets");
+    assert!(cifly::reach::reach_reverse(&graph, &sets, &ruletable, "Y", &[0]).is_err());
+}
+
+// compares against searches in the state graph from the start states of each node
+#[test]
+fn test_reach_by_source() {
+    let mut seed: u64 = 17;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+    let ruletable = cifly::Ruletable::from_multiline_string(
+        "
+EDGES --> <--, <->
+SETS X, Z
+COLORS init, yield
+START <-- [init] AT X
+START --> [init] AT Z
+OUTPUT ... [yield]
+
+... [init]  | ...      [yield] | next not in X
+... [yield] | <--, <-> [yield] | current not in Z",
+    )
+    .expect("should parse ruletable");
+
+    for num_nodes in [6, 40, 150] {
+        let mut edge_lists = HashMap::new();
+        for edge in ["-->", "<->"] {
+            let edges: Vec<_> = (0..2 * num_nodes)
+                .map(|_| (random(num_nodes) as usize, random(num_nodes) as usize))
+                .filter(|&(u, v)| u < v)
+                .collect();
+            edge_lists.insert(edge.to_owned(), edges);
+        }
+        let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+        let x: Vec<_> = (0..num_nodes as usize + 2)
+            .filter(|_| random(3) != 0)
+            .collect();
+        let z: Vec<_> = (0..num_nodes as usize).filter(|_| random(5) == 0).collect();
+        let sets = HashMap::from([("X".to_owned(), x), ("Z".to_owned(), z)]);
+        let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+        let state_graph = cifly::state_graph::state_graph(&graph, &sets, &ruletable);
+        let mut expected = Vec::new();
+        let mut sources: Vec<_> = state_graph
+            .states
+            .iter()
+            .filter(|s| s.is_start)
+            .map(|s| s.node)
+            .collect();
+        sources.dedup();
+        for v in sources {
+            let mut visited: Vec<_> = state_graph
+                .states
+                .iter()
+                .map(|s| s.is_start && s.node == v)
+                .collect();
+            let mut stack: Vec<_> = (0..visited.len()).filter(|&i| visited[i]).collect();
+            while let Some(i) = stack.pop() {
+                for &j in state_graph.adjacency[i].iter() {
+                    if !visited[j] {
+                        visited[j] = true;
+                        stack.push(j);
+                    }
+                }
+            }
+            let mut reached: Vec<_> = (0..visited.len())
+                .filter(|&i| visited[i] && state_graph.states[i].is_output)
+                .map(|i| state_graph.states[i].node)
+                .collect();
+            reached.dedup();
+            expected.push((v, reached));
+        }
+        assert!(num_nodes < 100 || expected.len() > 64);
+
+        assert_eq!(
+            cifly::reach::reach_by_source(&graph, &sets, &ruletable),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_state_graph() {
+    let ruletable_str = "
+EDGES --> <--, ---
+SETS X
+COLORS init, yield
+START ... [init] AT X
+OUTPUT ... [yield]
+
+... [init]  | ---      [yield] | next not in X
+... [yield] | ---, --> [yield] | next not in X";
+    let ruletable =
+        cifly::Ruletable::from_multiline_string(ruletable_str).expect("should parse ruletable");
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert("-->".to_owned(), vec![(2, 1), (2, 3), (3, 4), (5, 4)]);
+    edge_lists.insert("---".to_owned(), vec![(0, 1), (0, 2)]);
+    let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![1]);
+    let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+    let state_graph = cifly::state_graph::state_graph(&graph, &sets, &ruletable);
+    // 6 nodes, 3 edge types and 2 colors
+    assert_eq!(state_graph.states.len(), 36);
+    let start = state_graph
+        .find(1, "---", Some("init"))
+        .expect("state should exist");
+    assert!(state_graph.states[start].is_start);
+    let next = state_graph
+        .find(0, "---", Some("yield"))
+        .expect("state should exist");
+    assert_eq!(state_graph.states[next].label(), "(0, ---, yield)");
+    assert!(state_graph.adjacency[start].contains(&next));
+    assert!(state_graph.states[next].is_output);
+
+    let mut reached: Vec<_> = state_graph
+        .states
+        .iter()
+        .filter(|s| s.is_reachable && s.is_output)
+        .map(|s| s.node)
+        .collect();
+    reached.dedup();
+    let settings = cifly::Settings::new(false, false);
+    assert_eq!(
+        reached,
+        cifly::reach::reach(&graph, &sets, &ruletable, &settings)
+    );
+    // node 5 is only reached against the direction of its edge
+    assert!(state_graph
+        .states
+        .iter()
+        .filter(|s| s.node == 5)
+        .all(|s| !s.is_reachable));
+
+    let dot = state_graph.to_dot();
+    assert!(dot.starts_with("digraph states {"));
+    assert!(dot.contains(&format!(
+        "  {} [label=\"(1, ---, init)\", style=bold];",
+        start
+    )));
+    assert!(dot.contains(&format!("  {} -> {};", start, next)));
+    let graphml = state_graph.to_graphml();
+    assert!(graphml.contains("<data key=\"edge\">&lt;--</data>"));
+    assert!(graphml.contains(&format!(
+        "<edge source=\"s{}\" target=\"s{}\"/>",
+        start, next
+    )));
+}
+
+#[test]
+fn test_count_walks() {
+    let mut seed: u64 = 13;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+    let ruletable = cifly::Ruletable::descendants_admg();
+
+    for _ in 0..30 {
+        let directed: Vec<_> = (0..8)
+            .flat_map(|u| ((u + 1)..8).map(move |v| (u, v)))
+            .filter(|_| random(3) == 0)
+            .collect();
+        let mut edge_lists = HashMap::new();
+        edge_lists.insert("-->".to_owned(), directed.clone());
+        let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+        let mut sets = HashMap::new();
+        sets.insert("X".to_owned(), vec![0, 1]);
+        let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+        // number of directed paths from 0 or 1, nodes are in topological order
+        let mut expected = vec![0_u128; 8];
+        expected[0] = 1;
+        expected[1] = 1;
+        for v in 0..8 {
+            for &(u, w) in directed.iter() {
+                if w == v {
+                    expected[v] += expected[u];
+                }
+            }
+        }
+        let expected: Vec<_> = expected
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        assert_eq!(
+            cifly::paths::count_walks(&graph, &sets, &ruletable),
+            Some(expected)
+        );
+    }
+
+    // walks can turn around at colliders in Z
+    let ruletable = cifly::Ruletable::dconnected_admg();
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert("-->".to_owned(), vec![(0, 1)]);
+    let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![0]);
+    sets.insert("Z".to_owned(), vec![1]);
+    let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+    assert_eq!(cifly::paths::count_walks(&graph, &sets, &ruletable), None);
+}
+
+// arrowheads at u and v of every edge between u and v
+fn edge_marks(
+    directed: &[(usize, usize)],
+    bidirected: &[(usize, usize)],
+    u: usize,
+    v: usize,
+) -> Vec<(bool, bool)> {
+    let mut marks = Vec::new();
+    if directed.contains(&(u, v)) {
+        marks.push((false, true));
+    }
+    if directed.contains(&(v, u)) {
+        marks.push((true, false));
+    }
+    if bidirected.contains(&(u, v)) || bidirected.contains(&(v, u)) {
+        marks.push((true, true));
+    }
+    marks
+}
+
+#[test]
+fn test_simple_paths() {
+    let mut seed: u64 = 17;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+    let ruletable = cifly::Ruletable::dconnected_paths_admg();
+    let targets = [5, 6];
+
+    for round in 0..50 {
+        let mut directed = Vec::new();
+        let mut bidirected = Vec::new();
+        for u in 0..7 {
+            for v in (u + 1)..7 {
+                match random(6) {
+                    0 | 1 => directed.push((u, v)),
+                    2 => bidirected.push((u, v)),
+                    3 if round % 2 == 0 => {
+                        directed.push((u, v));
+                        bidirected.push((u, v));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let z: Vec<_> = (1..5).filter(|_| random(3) == 0).collect();
+        let mut ancestors = z.clone();
+        while let Some(&(u, _)) = directed
+            .iter()
+            .find(|(u, v)| ancestors.contains(v) && !ancestors.contains(u))
+        {
+            ancestors.push(u);
+        }
+
+        // a path along the given edges is open if every inner node is either a
+        // collider that is an ancestor of z or a non-collider not in z
+        let is_open = |path: &[usize], marks: &[(bool, bool)]| {
+            (1..path.len() - 1).all(|i| {
+                if marks[i - 1].1 && marks[i].0 {
+                    ancestors.contains(&path[i])
+                } else {
+                    !z.contains(&path[i])
+                }
+            })
+        };
+        let edge_name = |marks: (bool, bool)| match marks {
+            (false, true) => "-->",
+            (true, false) => "<--",
+            _ => "<->",
+        };
+
+        let mut expected = Vec::new();
+        let mut stack = vec![(vec![0], vec![])];
+        while let Some((path, marks)) = stack.pop() {
+            let last = *path.last().unwrap();
+            if path.len() > 1 && targets.contains(&last) && is_open(&path, &marks) {
+                expected.push(cifly::paths::Path {
+                    nodes: path.clone(),
+                    edges: marks.iter().map(|&m| edge_name(m).to_owned()).collect(),
+                });
+            }
+            for v in 0..7 {
+                if path.contains(&v) {
+                    continue;
+                }
+                for m in edge_marks(&directed, &bidirected, last, v) {
+                    let mut extended = (path.clone(), marks.clone());
+                    extended.0.push(v);
+                    extended.1.push(m);
+                    stack.push(extended);
+                }
+            }
+        }
+        // paths are ordered step by step, by node and then by edge
+        let edge_names = ruletable.edge_names();
+        expected.sort_by_key(|path| {
+            let edges = path
+                .edges
+                .iter()
+                .map(|e| edge_names.iter().position(|name| name == e));
+            let steps: Vec<_> = path.nodes[1..].iter().copied().zip(edges).collect();
+            (path.nodes[0], steps)
+        });
+
+        let mut edge_lists = HashMap::new();
+        edge_lists.insert("-->".to_owned(), directed.clone());
+        edge_lists.insert("<->".to_owned(), bidirected.clone());
+        let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+        let mut sets = HashMap::new();
+        sets.insert("X".to_owned(), vec![0]);
+        sets.insert("Z".to_owned(), z.clone());
+        sets.insert("A".to_owned(), ancestors.clone());
+        let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+
+        let mut options = cifly::paths::PathOptions::default();
+        let paths = cifly::paths::simple_paths(&graph, &sets, &ruletable, &targets, &options);
+        assert_eq!(paths, expected, "round {round}");
+
+        options.max_paths = Some(2);
+        let capped = cifly::paths::simple_paths(&graph, &sets, &ruletable, &targets, &options);
+        assert_eq!(capped, expected[..expected.len().min(2)]);
+
+        options.max_paths = None;
+        options.max_length = Some(2);
+        let short = cifly::paths::simple_paths(&graph, &sets, &ruletable, &targets, &options);
+        expected.retain(|path| path.nodes.len() <= 3);
+        assert_eq!(short, expected);
+    }
+}
+
+#[test]
+fn test_collider_ancestor_paths() {
+    // 0 --> 1 <-- 2 is open given the descendant 3 of the collider 1, but
+    // dconnected_admg only allows the walk 0 1 3 1 2 through it
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert("-->".to_owned(), vec![(0, 1), (2, 1), (1, 3)]);
+    let options = cifly::paths::PathOptions::default();
+    let walk_table = cifly::Ruletable::dconnected_admg();
+    let graph = cifly::Graph::new(&edge_lists, &walk_table).expect("should parse graph");
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![0]);
+    sets.insert("Z".to_owned(), vec![3]);
+    let parsed = cifly::Sets::new(&sets, &walk_table).expect("should parse sets");
+    assert!(cifly::reach::reaches(&graph, &parsed, &walk_table, 2));
+    assert!(cifly::paths::simple_paths(&graph, &parsed, &walk_table, &[2], &options).is_empty());
+
+    let path_table = cifly::Ruletable::dconnected_paths_admg();
+    let graph = cifly::Graph::new(&edge_lists, &path_table).expect("should parse graph");
+    sets.insert("A".to_owned(), vec![0, 1, 2, 3]);
+    let parsed = cifly::Sets::new(&sets, &path_table).expect("should parse sets");
+    assert!(cifly::reach::reaches(&graph, &parsed, &path_table, 2));
+    assert_eq!(
+        cifly::paths::simple_paths(&graph, &parsed, &path_table, &[2], &options),
+        vec![cifly::paths::Path {
+            nodes: vec![0, 1, 2],
+            edges: vec!["-->".to_owned(), "<--".to_owned()],
+        }]
+    );
+}
+
+#[test]
+fn test_backdoor_paths() {
+    let ruletable = cifly::Ruletable::backdoor_connected_dag();
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert(
+        "-->".to_owned(),
+        vec![(0, 1), (1, 3), (2, 0), (2, 3), (2, 4), (4, 0), (4, 3)],
+    );
+    let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+    let options = cifly::paths::PathOptions::default();
+
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![0]);
+    let parsed = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+    let nodes = |paths: Vec<cifly::paths::Path>| -> Vec<Vec<usize>> {
+        paths.into_iter().map(|path| path.nodes).collect()
+    };
+    assert_eq!(
+        nodes(cifly::paths::simple_paths(
+            &graph,
+            &parsed,
+            &ruletable,
+            &[3],
+            &options
+        )),
+        vec![
+            vec![0, 2, 3],
+            vec![0, 2, 4, 3],
+            vec![0, 4, 2, 3],
+            vec![0, 4, 3]
+        ]
+    );
+
+    sets.insert("W".to_owned(), vec![4]);
+    let parsed = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+    assert_eq!(
+        nodes(cifly::paths::simple_paths(
+            &graph,
+            &parsed,
+            &ruletable,
+            &[3],
+            &options
+        )),
+        vec![vec![0, 2, 3]]
+    );
+}
+
+#[test]
+fn test_parallel_edge_paths() {
+    let ruletable = cifly::Ruletable::dconnected_admg();
+    let mut edge_lists = HashMap::new();
+    edge_lists.insert("-->".to_owned(), vec![(0, 1), (1, 2)]);
+    edge_lists.insert("<->".to_owned(), vec![(0, 1)]);
+    let graph = cifly::Graph::new(&edge_lists, &ruletable).expect("should parse graph");
+    let mut sets = HashMap::new();
+    sets.insert("X".to_owned(), vec![0]);
+    let sets = cifly::Sets::new(&sets, &ruletable).expect("should parse sets");
+    let options = cifly::paths::PathOptions::default();
+    let paths = cifly::paths::simple_paths(&graph, &sets, &ruletable, &[2], &options);
+    let edges: Vec<_> = paths.iter().map(|path| path.edges.join(" ")).collect();
+    assert_eq!(edges, vec!["--> -->", "<-> -->"]);
+    assert!(paths.iter().all(|path| path.nodes == vec![0, 1, 2]));
+}
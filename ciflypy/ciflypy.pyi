@@ -21,6 +21,64 @@ class Ruletable:
         file path to all methods with a ruletable argument.
         """
         ...
+    @staticmethod
+    def bundled(name: str) -> Ruletable:
+        """
+        Returns one of the rule tables bundled with CIfly, such as the tables used in the examples on the CIfly website.
+
+        Parameters
+        ----------
+        name: Name of the bundled rule table, for example "dsep" or "possible_descendants_cpdag".
+
+        Returns
+        -------
+        Internal CIfly representation of the bundled rule table.
+        """
+        ...
+    @staticmethod
+    def bundled_names() -> List[str]:
+        """
+        Returns the names of all rule tables bundled with CIfly.
+        """
+        ...
+    @property
+    def edges(self) -> List[Tuple[str, str]]:
+        """
+        The edge types in declaration order as pairs of an edge type and its reverse, for example ("-->", "<--"). Symmetric edge types are their own reverse.
+        """
+        ...
+    @property
+    def colors(self) -> List[str]:
+        """
+        The colors in declaration order, empty if the ruletable has no colors.
+        """
+        ...
+    @property
+    def sets(self) -> List[str]:
+        """
+        The sets in declaration order.
+        """
+        ...
+    @property
+    def starts(self) -> List[Tuple[str, str, str | None]]:
+        """
+        The start states as tuples (set, edge, color) with patterns such as "..." expanded. The color is None if the ruletable has no colors.
+        """
+        ...
+    @property
+    def outputs(self) -> List[Tuple[str, str | None]]:
+        """
+        The output states as tuples (edge, color) with patterns expanded. The color is None if the ruletable has no colors.
+        """
+        ...
+    @property
+    def rules(
+        self,
+    ) -> List[Tuple[List[str], List[str], List[str], List[str], str]]:
+        """
+        The rules in order of precedence as tuples (prev_edges, prev_colors, next_edges, next_colors, expression). The patterns are expanded to the matched edge types and colors, the color lists are empty if the ruletable has no colors. The expression is given in ruletable syntax with definitions expanded.
+        """
+        ...
 
 class Graph:
     def __init__(
@@ -92,3 +150,227 @@ def reach(
     A list of all reachable nodes.
     """
     ...
+
+def reach_any(
+    graph: Mapping[str, List[Tuple[int, int]] | Set[Tuple[int, int]]] | Graph,
+    sets: Mapping[str, int | List[int] | Set[int]] | Sets,
+    ruletable: SupportsStr | Ruletable,
+    targets: int | List[int] | Set[int],
+    *,
+    table_as_string: bool = False,
+) -> List[int] | None:
+    """
+    Performs the CIfly algorithm specified in the passed ruletable until one of the targets is reached. This is usually much faster than reach if only the reachability of the targets is of interest, for example for d-connection tests.
+
+    Parameters
+    ----------
+    graph: A dictionary mapping edge types to edge lists.
+    sets: A dictionary mapping set names to a list of elements.
+    ruletable: Path to the ruletable file.
+    targets: The target nodes.
+    table_as_string: Enable passing the ruletable as multi-line string. Default value is False.
+
+    Returns
+    -------
+    A list of the nodes of a walk from a start node to the first reached target, ending with the target, or None if no target is reachable.
+    """
+    ...
+
+def reaches(
+    graph: Mapping[str, List[Tuple[int, int]] | Set[Tuple[int, int]]] | Graph,
+    sets: Mapping[str, int | List[int] | Set[int]] | Sets,
+    ruletable: SupportsStr | Ruletable,
+    node: int,
+    *,
+    table_as_string: bool = False,
+) -> bool:
+    """
+    Checks whether the node is reachable according to the passed ruletable, stopping as soon as it is reached.
+
+    Parameters
+    ----------
+    graph: A dictionary mapping edge types to edge lists.
+    sets: A dictionary mapping set names to a list of elements.
+    ruletable: Path to the ruletable file.
+    node: The target node.
+    table_as_string: Enable passing the ruletable as multi-line string. Default value is False.
+
+    Returns
+    -------
+    True if the node is reachable and False otherwise.
+    """
+    ...
+
+def is_cpdag_adjustment(
+    graph: Mapping[str, List[Tuple[int, int]] | Set[Tuple[int, int]]],
+    X: int | List[int] | Set[int],
+    Y: int | List[int] | Set[int],
+    W: int | List[int] | Set[int],
+) -> bool:
+    """
+    Checks whether W is a valid adjustment set relative to X and Y in a CPDAG.
+
+    Parameters
+    ----------
+    graph: A dictionary mapping edge types "-->" and "---" to edge lists.
+    X: The treatment nodes.
+    Y: The outcome nodes.
+    W: The candidate adjustment set.
+
+    Returns
+    -------
+    True if W is a valid adjustment set and False otherwise.
+    """
+    ...
+
+def optimal_adjustment(
+    graph: Mapping[str, List[Tuple[int, int]] | Set[Tuple[int, int]]],
+    X: int | List[int] | Set[int],
+    Y: int | List[int] | Set[int],
+) -> List[int] | None:
+    """
+    Constructs the optimal adjustment set relative to X and Y in a CPDAG.
+
+    Parameters
+    ----------
+    graph: A dictionary mapping edge types "-->" and "---" to edge lists.
+    X: The treatment nodes.
+    Y: The outcome nodes.
+
+    Returns
+    -------
+    A list of the nodes in the optimal adjustment set or None if no valid adjustment set exists.
+    """
+    ...
+
+def parent_aid(
+    p: int,
+    g_true: Mapping[str, List[Tuple[int, int]] | Set[Tuple[int, int]]],
+    g_guess: Mapping[str, List[Tuple[int, int]] | Set[Tuple[int, int]]],
+) -> int:
+    """
+    Computes the parent adjustment distance between two CPDAGs.
+
+    Parameters
+    ----------
+    p: The number of nodes.
+    g_true: A dictionary mapping edge types "-->" and "---" to edge lists of the true graph.
+    g_guess: A dictionary mapping edge types "-->" and "---" to edge lists of the guessed graph.
+
+    Returns
+    -------
+    The number of node pairs for which the guessed graph implies a wrong adjustment set.
+    """
+    ...
+
+def frontdoor(
+    graph: Mapping[str, List[Tuple[int, int]] | Set[Tuple[int, int]]],
+    X: int | List[int] | Set[int],
+    Y: int | List[int] | Set[int],
+    I: int | List[int] | Set[int],
+    R: int | List[int] | Set[int],
+) -> List[int] | None:
+    """
+    Finds the largest front-door adjustment set Z relative to X and Y in a DAG with I ⊆ Z ⊆ R.
+
+    Parameters
+    ----------
+    graph: A dictionary mapping edge type "-->" to an edge list.
+    X: The treatment nodes.
+    Y: The outcome nodes.
+    I: Nodes that have to be contained in the front-door adjustment set.
+    R: Nodes that may be contained in the front-door adjustment set.
+
+    Returns
+    -------
+    A list of the nodes in the front-door adjustment set or None if no such set exists.
+    """
+    ...
+
+def find_nearest_dsep(
+    graph: Mapping[str, List[Tuple[int, int]] | Set[Tuple[int, int]]],
+    X: int | List[int] | Set[int],
+    Y: int | List[int] | Set[int],
+    I: int | List[int] | Set[int],
+    R: int | List[int] | Set[int],
+) -> List[int] | None:
+    """
+    Finds the d-separator Z of X and Y in an ADMG with I ⊆ Z ⊆ R that is nearest to X.
+
+    Parameters
+    ----------
+    graph: A dictionary mapping edge types "-->" and "<->" to edge lists.
+    X: The first set of nodes.
+    Y: The second set of nodes.
+    I: Nodes that have to be contained in the separator.
+    R: Nodes that may be contained in the separator.
+
+    Returns
+    -------
+    A list of the nodes in the separator or None if no such separator exists.
+    """
+    ...
+
+def find_minimal_dsep(
+    graph: Mapping[str, List[Tuple[int, int]] | Set[Tuple[int, int]]],
+    X: int | List[int] | Set[int],
+    Y: int | List[int] | Set[int],
+    I: int | List[int] | Set[int],
+    R: int | List[int] | Set[int],
+) -> List[int] | None:
+    """
+    Finds a minimal d-separator Z of X and Y in an ADMG with I ⊆ Z ⊆ R.
+
+    Parameters
+    ----------
+    graph: A dictionary mapping edge types "-->" and "<->" to edge lists.
+    X: The first set of nodes.
+    Y: The second set of nodes.
+    I: Nodes that have to be contained in the separator.
+    R: Nodes that may be contained in the separator.
+
+    Returns
+    -------
+    A list of the nodes in the separator or None if no such separator exists.
+    """
+    ...
+
+def optimal_instrument(
+    graph: Mapping[str, List[Tuple[int, int]] | Set[Tuple[int, int]]],
+    x: int,
+    y: int,
+) -> Tuple[List[int], List[int]] | None:
+    """
+    Finds the optimal conditional instrument for the effect of x on y in an ADMG. The instrument is only guaranteed to be optimal if there is no adjustment set relative to x and y.
+
+    Parameters
+    ----------
+    graph: A dictionary mapping edge types "-->" and "<->" to edge lists.
+    x: The treatment node.
+    y: The outcome node.
+
+    Returns
+    -------
+    A tuple of the list of instruments and the list of conditioning nodes or None if the criterion yields no instrument.
+    """
+    ...
+
+def sound_and_complete_instrument(
+    graph: Mapping[str, List[Tuple[int, int]] | Set[Tuple[int, int]]],
+    x: int,
+    y: int,
+) -> Tuple[List[int], List[int]] | None:
+    """
+    Finds a conditional instrument with a single instrument for the effect of x on y in an ADMG.
+
+    Parameters
+    ----------
+    graph: A dictionary mapping edge types "-->" and "<->" to edge lists.
+    x: The treatment node.
+    y: The outcome node.
+
+    Returns
+    -------
+    A tuple of the list containing the instrument and the list of conditioning nodes or None if no such instrument exists.
+    """
+    ...
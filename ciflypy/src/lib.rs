@@ -8,6 +8,16 @@ use pyo3::types::{PyDict, PyString};
 #[pymodule]
 fn ciflypy(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(reach, m)?)?;
+    m.add_function(wrap_pyfunction!(reach_any, m)?)?;
+    m.add_function(wrap_pyfunction!(reaches, m)?)?;
+    m.add_function(wrap_pyfunction!(is_cpdag_adjustment, m)?)?;
+    m.add_function(wrap_pyfunction!(optimal_adjustment, m)?)?;
+    m.add_function(wrap_pyfunction!(parent_aid, m)?)?;
+    m.add_function(wrap_pyfunction!(frontdoor, m)?)?;
+    m.add_function(wrap_pyfunction!(find_nearest_dsep, m)?)?;
+    m.add_function(wrap_pyfunction!(find_minimal_dsep, m)?)?;
+    m.add_function(wrap_pyfunction!(optimal_instrument, m)?)?;
+    m.add_function(wrap_pyfunction!(sound_and_complete_instrument, m)?)?;
     m.add_class::<Ruletable>()?;
     m.add_class::<Graph>()?;
     m.add_class::<Sets>()?;
@@ -53,7 +63,71 @@ fn reach(
     verbose: bool,
 ) -> PyResult<Vec<usize>> {
     let settings = cifly::Settings::new(verbose, false);
+    with_instance(&graph, &sets, &ruletable, table_as_string, |g, s, r| {
+        cifly::reach::reach(g, s, r, &settings)
+    })
+}
+
+/// Perform the CIfly algorithm specified in the passed ruletable until one of the targets is reached.
+///
+/// Parameters:
+///     graph: A dictionary mapping edge types to edge lists.
+///     sets: A dictionary mapping set names to a list of elements.
+///     ruletable: Path to ruletable file.
+///     targets: The target nodes.
+///     table_as_string: Optional keyword argument to enable passing the ruletable as multi-line string. Default value is False.
+///
+/// Returns:
+///     A list of the nodes of a walk from a start node to the first reached target or None if no target is reachable.
+#[pyfunction]
+#[pyo3(signature = (graph, sets, ruletable, targets, *,  table_as_string=false))]
+fn reach_any(
+    graph: Bound<'_, PyAny>,
+    sets: Bound<'_, PyAny>,
+    ruletable: Bound<'_, PyAny>,
+    targets: Bound<'_, PyAny>,
+    table_as_string: bool,
+) -> PyResult<Option<Vec<usize>>> {
+    let targets = to_node_list(&targets)?;
+    with_instance(&graph, &sets, &ruletable, table_as_string, |g, s, r| {
+        cifly::reach::reach_any(g, s, r, &targets)
+    })
+}
+
+/// Check whether the node is reachable according to the passed ruletable, stopping as soon as it is reached.
+///
+/// Parameters:
+///     graph: A dictionary mapping edge types to edge lists.
+///     sets: A dictionary mapping set names to a list of elements.
+///     ruletable: Path to ruletable file.
+///     node: The target node.
+///     table_as_string: Optional keyword argument to enable passing the ruletable as multi-line string. Default value is False.
+///
+/// Returns:
+///     True if the node is reachable and False otherwise.
+#[pyfunction]
+#[pyo3(signature = (graph, sets, ruletable, node, *,  table_as_string=false))]
+fn reaches(
+    graph: Bound<'_, PyAny>,
+    sets: Bound<'_, PyAny>,
+    ruletable: Bound<'_, PyAny>,
+    node: usize,
+    table_as_string: bool,
+) -> PyResult<bool> {
+    with_instance(&graph, &sets, &ruletable, table_as_string, |g, s, r| {
+        cifly::reach::reaches(g, s, r, node)
+    })
+}
 
+// Resolves the graph, sets and ruletable arguments, which are either parsed
+// objects or Python values, and passes them to f.
+fn with_instance<T>(
+    graph: &Bound<'_, PyAny>,
+    sets: &Bound<'_, PyAny>,
+    ruletable: &Bound<'_, PyAny>,
+    table_as_string: bool,
+    f: impl FnOnce(&cifly::Graph, &cifly::Sets, &cifly::Ruletable) -> T,
+) -> PyResult<T> {
     let borrow_ruletable;
     let parsed_ruletable;
     let ruletable_ref = if let Ok(rt) = ruletable.downcast::<Ruletable>() {
@@ -97,9 +171,207 @@ fn reach(
         ));
     };
 
-    let reached = cifly::reach::reach(graph_ref, sets_ref, ruletable_ref, &settings);
+    Ok(f(graph_ref, sets_ref, ruletable_ref))
+}
+
+/// Check whether W is a valid adjustment set relative to X and Y in a CPDAG.
+///
+/// Parameters:
+///     graph: A dictionary mapping edge types "-->" and "---" to edge lists.
+///     X: The treatment nodes.
+///     Y: The outcome nodes.
+///     W: The candidate adjustment set.
+///
+/// Returns:
+///     True if W is a valid adjustment set and False otherwise.
+#[pyfunction]
+#[allow(non_snake_case)]
+#[pyo3(signature = (graph, X, Y, W))]
+fn is_cpdag_adjustment(
+    graph: Bound<'_, PyDict>,
+    X: Bound<'_, PyAny>,
+    Y: Bound<'_, PyAny>,
+    W: Bound<'_, PyAny>,
+) -> PyResult<bool> {
+    cifly::algorithms::is_cpdag_adjustment(
+        &to_edge_lists(&graph)?,
+        &to_node_list(&X)?,
+        &to_node_list(&Y)?,
+        &to_node_list(&W)?,
+    )
+    .map_err(graph_error)
+}
+
+/// Construct the optimal adjustment set relative to X and Y in a CPDAG.
+///
+/// Parameters:
+///     graph: A dictionary mapping edge types "-->" and "---" to edge lists.
+///     X: The treatment nodes.
+///     Y: The outcome nodes.
+///
+/// Returns:
+///     A list of the nodes in the optimal adjustment set or None if no valid adjustment set exists.
+#[pyfunction]
+#[allow(non_snake_case)]
+#[pyo3(signature = (graph, X, Y))]
+fn optimal_adjustment(
+    graph: Bound<'_, PyDict>,
+    X: Bound<'_, PyAny>,
+    Y: Bound<'_, PyAny>,
+) -> PyResult<Option<Vec<usize>>> {
+    cifly::algorithms::optimal_adjustment(
+        &to_edge_lists(&graph)?,
+        &to_node_list(&X)?,
+        &to_node_list(&Y)?,
+    )
+    .map_err(graph_error)
+}
+
+/// Compute the parent adjustment distance between two CPDAGs.
+///
+/// Parameters:
+///     p: The number of nodes.
+///     g_true: A dictionary mapping edge types "-->" and "---" to edge lists of the true graph.
+///     g_guess: A dictionary mapping edge types "-->" and "---" to edge lists of the guessed graph.
+///
+/// Returns:
+///     The number of node pairs for which the guessed graph implies a wrong adjustment set.
+#[pyfunction]
+fn parent_aid(p: usize, g_true: Bound<'_, PyDict>, g_guess: Bound<'_, PyDict>) -> PyResult<usize> {
+    cifly::algorithms::parent_aid(p, &to_edge_lists(&g_true)?, &to_edge_lists(&g_guess)?)
+        .map_err(graph_error)
+}
+
+/// Find the largest front-door adjustment set Z relative to X and Y in a DAG with I ⊆ Z ⊆ R.
+///
+/// Parameters:
+///     graph: A dictionary mapping edge type "-->" to an edge list.
+///     X: The treatment nodes.
+///     Y: The outcome nodes.
+///     I: Nodes that have to be contained in the front-door adjustment set.
+///     R: Nodes that may be contained in the front-door adjustment set.
+///
+/// Returns:
+///     A list of the nodes in the front-door adjustment set or None if no such set exists.
+#[pyfunction]
+#[allow(non_snake_case)]
+#[pyo3(signature = (graph, X, Y, I, R))]
+fn frontdoor(
+    graph: Bound<'_, PyDict>,
+    X: Bound<'_, PyAny>,
+    Y: Bound<'_, PyAny>,
+    I: Bound<'_, PyAny>,
+    R: Bound<'_, PyAny>,
+) -> PyResult<Option<Vec<usize>>> {
+    cifly::algorithms::frontdoor(
+        &to_edge_lists(&graph)?,
+        &to_node_list(&X)?,
+        &to_node_list(&Y)?,
+        &to_node_list(&I)?,
+        &to_node_list(&R)?,
+    )
+    .map_err(graph_error)
+}
+
+/// Find the d-separator Z of X and Y in an ADMG with I ⊆ Z ⊆ R that is nearest to X.
+///
+/// Parameters:
+///     graph: A dictionary mapping edge types "-->" and "<->" to edge lists.
+///     X: The first set of nodes.
+///     Y: The second set of nodes.
+///     I: Nodes that have to be contained in the separator.
+///     R: Nodes that may be contained in the separator.
+///
+/// Returns:
+///     A list of the nodes in the separator or None if no such separator exists.
+#[pyfunction]
+#[allow(non_snake_case)]
+#[pyo3(signature = (graph, X, Y, I, R))]
+fn find_nearest_dsep(
+    graph: Bound<'_, PyDict>,
+    X: Bound<'_, PyAny>,
+    Y: Bound<'_, PyAny>,
+    I: Bound<'_, PyAny>,
+    R: Bound<'_, PyAny>,
+) -> PyResult<Option<Vec<usize>>> {
+    cifly::algorithms::find_nearest_dsep(
+        &to_edge_lists(&graph)?,
+        &to_node_list(&X)?,
+        &to_node_list(&Y)?,
+        &to_node_list(&I)?,
+        &to_node_list(&R)?,
+    )
+    .map_err(graph_error)
+}
+
+/// Find a minimal d-separator Z of X and Y in an ADMG with I ⊆ Z ⊆ R.
+///
+/// Parameters:
+///     graph: A dictionary mapping edge types "-->" and "<->" to edge lists.
+///     X: The first set of nodes.
+///     Y: The second set of nodes.
+///     I: Nodes that have to be contained in the separator.
+///     R: Nodes that may be contained in the separator.
+///
+/// Returns:
+///     A list of the nodes in the separator or None if no such separator exists.
+#[pyfunction]
+#[allow(non_snake_case)]
+#[pyo3(signature = (graph, X, Y, I, R))]
+fn find_minimal_dsep(
+    graph: Bound<'_, PyDict>,
+    X: Bound<'_, PyAny>,
+    Y: Bound<'_, PyAny>,
+    I: Bound<'_, PyAny>,
+    R: Bound<'_, PyAny>,
+) -> PyResult<Option<Vec<usize>>> {
+    cifly::algorithms::find_minimal_dsep(
+        &to_edge_lists(&graph)?,
+        &to_node_list(&X)?,
+        &to_node_list(&Y)?,
+        &to_node_list(&I)?,
+        &to_node_list(&R)?,
+    )
+    .map_err(graph_error)
+}
+
+/// Find the optimal conditional instrument for the effect of x on y in an ADMG.
+///
+/// The instrument is only guaranteed to be optimal if there is no adjustment set relative to x and y.
+///
+/// Parameters:
+///     graph: A dictionary mapping edge types "-->" and "<->" to edge lists.
+///     x: The treatment node.
+///     y: The outcome node.
+///
+/// Returns:
+///     A tuple of the list of instruments and the list of conditioning nodes or None if the criterion yields no instrument.
+#[pyfunction]
+fn optimal_instrument(
+    graph: Bound<'_, PyDict>,
+    x: usize,
+    y: usize,
+) -> PyResult<Option<cifly::algorithms::Instrument>> {
+    cifly::algorithms::optimal_instrument(&to_edge_lists(&graph)?, x, y).map_err(graph_error)
+}
 
-    Ok(reached)
+/// Find a conditional instrument with a single instrument for the effect of x on y in an ADMG.
+///
+/// Parameters:
+///     graph: A dictionary mapping edge types "-->" and "<->" to edge lists.
+///     x: The treatment node.
+///     y: The outcome node.
+///
+/// Returns:
+///     A tuple of the list containing the instrument and the list of conditioning nodes or None if no such instrument exists.
+#[pyfunction]
+fn sound_and_complete_instrument(
+    graph: Bound<'_, PyDict>,
+    x: usize,
+    y: usize,
+) -> PyResult<Option<cifly::algorithms::Instrument>> {
+    cifly::algorithms::sound_and_complete_instrument(&to_edge_lists(&graph)?, x, y)
+        .map_err(graph_error)
 }
 
 /// Constructs an internal CIfly ruletable representation. Mostly recommended for improving performance if the same ruletable is used multiple times.
@@ -127,8 +399,101 @@ impl Ruletable {
             ))
         }
     }
+
+    /// Returns one of the rule tables bundled with CIfly.
+    ///
+    /// Parameters:
+    ///     name: Name of the bundled rule table, for example "dsep" or "possible_descendants_cpdag".
+    ///
+    /// Returns:
+    ///     Internal CIfly representation of the bundled rule table.
+    #[staticmethod]
+    fn bundled(name: &str) -> PyResult<Self> {
+        cifly::Ruletable::bundled(name)
+            .map(Ruletable)
+            .ok_or_else(|| {
+                PyRuntimeError::new_err(format!(
+                    "error reading ruletable: no bundled ruletable named {}, available are: {}",
+                    name,
+                    cifly::tables::NAMES.join(", ")
+                ))
+            })
+    }
+
+    /// Returns the names of all rule tables bundled with CIfly.
+    #[staticmethod]
+    fn bundled_names() -> Vec<&'static str> {
+        cifly::tables::NAMES.to_vec()
+    }
+
+    /// The edge types in declaration order as pairs of an edge type and its reverse.
+    #[getter]
+    fn edges(&self) -> Vec<(String, String)> {
+        self.0
+            .edge_names()
+            .into_iter()
+            .map(|edge| {
+                let reverse = self.0.reverse_edge_name(&edge).unwrap_or_default();
+                (edge, reverse)
+            })
+            .collect()
+    }
+
+    /// The colors in declaration order, empty if the ruletable has no colors.
+    #[getter]
+    fn colors(&self) -> Vec<String> {
+        self.0.color_names()
+    }
+
+    /// The sets in declaration order.
+    #[getter]
+    fn sets(&self) -> Vec<String> {
+        self.0.set_names()
+    }
+
+    /// The start states as tuples (set, edge, color), with color None if the ruletable has no colors.
+    #[getter]
+    fn starts(&self) -> Vec<(String, String, Option<String>)> {
+        self.0
+            .start_specs()
+            .into_iter()
+            .map(|start| (start.set, start.edge, start.color))
+            .collect()
+    }
+
+    /// The output states as tuples (edge, color), with color None if the ruletable has no colors.
+    #[getter]
+    fn outputs(&self) -> Vec<(String, Option<String>)> {
+        self.0
+            .output_specs()
+            .into_iter()
+            .map(|output| (output.edge, output.color))
+            .collect()
+    }
+
+    /// The rules in order of precedence as tuples (prev_edges, prev_colors, next_edges, next_colors, expression).
+    #[getter]
+    fn rules(&self) -> Vec<RuleTuple> {
+        let set_names = self.0.set_names();
+        self.0
+            .rule_specs()
+            .into_iter()
+            .map(|rule| {
+                (
+                    rule.prev_edges,
+                    rule.prev_colors,
+                    rule.next_edges,
+                    rule.next_colors,
+                    rule.expression.to_ruletable_string(&set_names),
+                )
+            })
+            .collect()
+    }
 }
 
+// edge types and colors of the previous and next state and the expression of a rule
+type RuleTuple = (Vec<String>, Vec<String>, Vec<String>, Vec<String>, String);
+
 /// Constructs an internal CIfly graph representation. Mostly recommended for improving performance if the same graph is used multiple times.
 ///
 /// Parameters:
@@ -231,6 +596,10 @@ fn to_ruletable(
 }
 
 fn to_graph(graph: &Bound<'_, PyDict>, ruletable: &cifly::Ruletable) -> PyResult<cifly::Graph> {
+    cifly::Graph::new(&to_edge_lists(graph)?, ruletable).map_err(graph_error)
+}
+
+fn to_edge_lists(graph: &Bound<'_, PyDict>) -> PyResult<HashMap<String, Vec<(usize, usize)>>> {
     let mut edge_lists = HashMap::new();
     for (edge_string, edges) in graph.iter() {
         let edge_string: String = edge_string.extract()?;
@@ -240,21 +609,27 @@ fn to_graph(graph: &Bound<'_, PyDict>, ruletable: &cifly::Ruletable) -> PyResult
         };
         edge_lists.insert(edge_string, edges);
     }
-    cifly::Graph::new(&edge_lists, ruletable)
-        .map_err(|err| PyRuntimeError::new_err(format!("Error reading graph. \n{}", err)))
+    Ok(edge_lists)
+}
+
+fn graph_error(err: cifly::ParseGraphError) -> PyErr {
+    PyRuntimeError::new_err(format!("Error reading graph. \n{}", err))
 }
 
 fn to_sets(sets: &Bound<'_, PyDict>, ruletable: &cifly::Ruletable) -> PyResult<cifly::Sets> {
     let mut set_lists = HashMap::new();
     for (set_string, set) in sets.iter() {
         let set_string: String = set_string.extract()?;
-        let set = match set.extract::<NodeSet>().unwrap() {
-            NodeSet::Int(u) => vec![u],
-            NodeSet::List(l) => l,
-            NodeSet::Set(s) => Vec::from_iter(s),
-        };
-        set_lists.insert(set_string, set);
+        set_lists.insert(set_string, to_node_list(&set)?);
     }
     cifly::Sets::new(&set_lists, ruletable)
         .map_err(|err| PyRuntimeError::new_err(format!("Error reading sets. \n{}", err)))
 }
+
+fn to_node_list(set: &Bound<'_, PyAny>) -> PyResult<Vec<usize>> {
+    Ok(match set.extract::<NodeSet>()? {
+        NodeSet::Int(u) => vec![u],
+        NodeSet::List(l) => l,
+        NodeSet::Set(s) => Vec::from_iter(s),
+    })
+}
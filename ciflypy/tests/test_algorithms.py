@@ -0,0 +1,46 @@
+import ciflypy as cf
+
+
+def test_adjustment():
+    cpdag = {
+        "-->": [(1, 4), (3, 4), (2, 4)],
+        "---": [(0, 1), (0, 3), (1, 3)],
+    }
+
+    assert cf.optimal_adjustment(cpdag, 2, 4) == [1, 3]
+    assert cf.optimal_adjustment(cpdag, 3, 4) is None
+
+    cpdag = {
+        "-->": [(0, 2), (1, 2), (2, 5), (3, 2), (3, 5), (4, 5)],
+        "---": [(0, 1), (1, 3), (1, 4), (3, 4)],
+    }
+
+    assert cf.is_cpdag_adjustment(cpdag, 2, 5, [1, 3])
+    assert not cf.is_cpdag_adjustment(cpdag, 2, 5, [1])
+
+
+def test_parent_aid():
+    g_true = {"-->": [(0, 1), (1, 2), (0, 3), (3, 2)]}
+    g_guess = {"-->": [(0, 1), (1, 2), (3, 0), (3, 2)]}
+
+    assert cf.parent_aid(4, g_true, g_guess) == 5
+
+
+def test_frontdoor():
+    dag = {"-->": [(0, 1), (1, 2), (3, 0), (3, 2)]}
+
+    assert cf.frontdoor(dag, 0, 2, [], [1]) == [1]
+
+
+def test_separators():
+    admg = {"-->": [(1, 0), (1, 2), (2, 4), (3, 2), (3, 4)], "<->": [(0, 2)]}
+
+    assert cf.find_minimal_dsep(admg, 0, 4, [], {0, 1, 2, 3, 4}) == [2, 3]
+    assert cf.find_nearest_dsep(admg, 0, 4, [], [1]) is None
+
+
+def test_instruments():
+    admg = {"-->": [(0, 1), (0, 2), (2, 3)], "<->": [(1, 3), (2, 3)]}
+
+    assert cf.optimal_instrument(admg, 2, 3) is None
+    assert cf.sound_and_complete_instrument(admg, 2, 3) == ([0], [])
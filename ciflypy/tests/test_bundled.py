@@ -0,0 +1,32 @@
+import ciflypy as cf
+
+
+def test_bundled_dsep():
+    edgelist = {"-->": [(0, 1), (2, 1), (1, 3)]}
+    sets = {"X": 0, "Z": [3]}
+
+    reach_all = cf.reach(edgelist, sets, cf.Ruletable.bundled("dsep"))
+
+    assert set(reach_all) == {0, 1, 2, 3}
+
+
+def test_bundled_names():
+    names = cf.Ruletable.bundled_names()
+
+    assert "dsep" in names
+    for name in names:
+        cf.Ruletable.bundled(name)
+
+
+def test_ruletable_introspection():
+    ruletable = cf.Ruletable.bundled("dsep")
+
+    assert ruletable.edges == [("-->", "<--"), ("<--", "-->")]
+    assert ruletable.colors == []
+    assert ruletable.sets == ["X", "Z"]
+    assert ruletable.starts == [("X", "<--", None)]
+    assert ruletable.outputs == [("-->", None), ("<--", None)]
+    assert ruletable.rules == [
+        (["-->"], [], ["<--"], [], "current in Z"),
+        (["-->", "<--"], [], ["-->", "<--"], [], "current not in Z"),
+    ]
@@ -29,3 +29,12 @@ def test_dsep_collider_blocked():
     reach_three = cf.reach(edgeset, sets, dsep_table)
 
     assert set(reach_three) == {0, 1, 3}
+
+
+def test_dsep_reach_any():
+    edgelist = {"-->": [(0, 1), (2, 1), (1, 3)]}
+
+    assert cf.reach_any(edgelist, {"X": 0, "Z": [3]}, dsep_table, [2]) == [0, 1, 2]
+    assert cf.reach_any(edgelist, {"X": 0, "Z": []}, dsep_table, 2) is None
+    assert cf.reaches(edgelist, {"X": 0, "Z": [3]}, dsep_table, 2)
+    assert not cf.reaches(edgelist, {"X": 0, "Z": []}, dsep_table, 2)
@@ -7,9 +7,22 @@ use extendr_api::prelude::*;
 extendr_module! {
     mod ciflyr;
     fn reach;
+    fn reachAny;
+    fn reaches;
     fn parseRuletable;
+    fn bundledRuletable;
+    fn bundledRuletableNames;
+    fn ruletableInfo;
     fn parseGraph;
     fn parseSets;
+    fn isCpdagAdjustment;
+    fn optimalAdjustment;
+    fn parentAid;
+    fn frontdoor;
+    fn findNearestDsep;
+    fn findMinimalDsep;
+    fn optimalInstrument;
+    fn soundAndCompleteInstrument;
     impl Ruletable;
     impl Graph;
     impl Sets;
@@ -48,9 +61,85 @@ fn reach(
     #[default = "FALSE"] verbose: bool,
 ) -> Result<Vec<usize>> {
     let settings = cifly::Settings::new(verbose, true);
+    with_instance(&graph, &sets, &ruletable, tableAsString, |g, s, r| {
+        from_node_ids(cifly::reach::reach(g, s, r, &settings))
+    })
+}
+
+/// Perform the CIfly algorithm specified in the passed ruletable until one of the targets is reached.
+///
+/// Runs the same algorithm as reach but stops as soon as one of the target nodes is reached. This is usually much faster than reach if only the reachability of the targets is of interest, for example for d-connection tests.
+///
+/// @param graph A list mapping edge types to edge lists stored in matrix format.
+/// @param sets A list mapping set names to a list of elements.
+/// @param ruletable Path to a ruletable file.
+/// @param targets A vector of the target nodes.
+/// @param tableAsString Optional argument to enable passing the ruletable as multi-line string. Default value is FALSE.
+/// @return A vector of the nodes of a walk from a start node to the first reached target, ending with the target, or NULL if no target is reachable.
+/// @examples
+/// dsepTable <- "
+///     EDGES --> <--
+///     SETS X, Z
+///     START <-- AT X
+///     OUTPUT ...
+///     --> | <-- | current in Z
+///     ... | ... | current not in Z
+/// "
+///
+/// edgelist <- list("-->" = rbind(c(1, 2), c(3, 2), c(2, 4)))
+/// sets <- list("X" = c(1), "Z" = c(4))
+/// reachAny(edgelist, sets, dsepTable, c(3), tableAsString=TRUE)
+/// @export
+#[extendr]
+fn reachAny(
+    graph: Robj,
+    sets: Robj,
+    ruletable: Robj,
+    targets: Robj,
+    #[default = "FALSE"] tableAsString: bool,
+) -> Result<Robj> {
+    let targets = to_node_ids(&targets)?;
+    with_instance(&graph, &sets, &ruletable, tableAsString, |g, s, r| {
+        from_optional_node_ids(cifly::reach::reach_any(g, s, r, &targets))
+    })
+}
 
+/// Check whether a node is reachable according to the passed ruletable.
+///
+/// Runs the same algorithm as reach but stops as soon as the node is reached.
+///
+/// @param graph A list mapping edge types to edge lists stored in matrix format.
+/// @param sets A list mapping set names to a list of elements.
+/// @param ruletable Path to a ruletable file.
+/// @param node The target node.
+/// @param tableAsString Optional argument to enable passing the ruletable as multi-line string. Default value is FALSE.
+/// @return TRUE if the node is reachable and FALSE otherwise.
+/// @export
+#[extendr]
+fn reaches(
+    graph: Robj,
+    sets: Robj,
+    ruletable: Robj,
+    node: Robj,
+    #[default = "FALSE"] tableAsString: bool,
+) -> Result<bool> {
+    let node = to_node_id(&node)?;
+    with_instance(&graph, &sets, &ruletable, tableAsString, |g, s, r| {
+        cifly::reach::reaches(g, s, r, node)
+    })
+}
+
+// Resolves the graph, sets and ruletable arguments, which are either parsed
+// objects or R values, and passes them to f.
+fn with_instance<T>(
+    graph: &Robj,
+    sets: &Robj,
+    ruletable: &Robj,
+    tableAsString: bool,
+    f: impl FnOnce(&cifly::Graph, &cifly::Sets, &cifly::Ruletable) -> T,
+) -> Result<T> {
     let parsed_ruletable;
-    let ruletable_ref = match <&Ruletable>::try_from(&ruletable) {
+    let ruletable_ref = match <&Ruletable>::try_from(ruletable) {
         Ok(rt) => &rt.0,
         Err(_) => {
             parsed_ruletable = to_ruletable(
@@ -64,29 +153,24 @@ fn reach(
     };
 
     let parsed_graph;
-    let graph_ref = match <&Graph>::try_from(&graph) {
+    let graph_ref = match <&Graph>::try_from(graph) {
         Ok(g) => &g.0,
         Err(_) => {
-            parsed_graph = to_graph(&graph, ruletable_ref)?;
+            parsed_graph = to_graph(graph, ruletable_ref)?;
             &parsed_graph
         }
     };
 
     let parsed_sets;
-    let sets_ref = match <&Sets>::try_from(&sets) {
+    let sets_ref = match <&Sets>::try_from(sets) {
         Ok(z) => &z.0,
         Err(_) => {
-            parsed_sets = to_sets(&sets, ruletable_ref)?;
+            parsed_sets = to_sets(sets, ruletable_ref)?;
             &parsed_sets
         }
     };
 
-    Ok(
-        cifly::reach::reach(graph_ref, sets_ref, ruletable_ref, &settings)
-            .iter()
-            .map(|&x| x + 1)
-            .collect(),
-    )
+    Ok(f(graph_ref, sets_ref, ruletable_ref))
 }
 
 #[extendr]
@@ -127,6 +211,116 @@ fn parseRuletable(ruletable: Robj, #[default = "FALSE"] tableAsString: bool) ->
     )?))
 }
 
+/// Obtain a ruletable bundled with CIfly.
+///
+/// Obtain the internal representation of one of the ruletables bundled with CIfly, such as the ruletables used in the examples on the CIfly website. The names of all bundled ruletables are returned by `bundledRuletableNames()`. The ruletable object can be passed to all methods with a ruletable argument.
+///
+/// @param name Name of the bundled ruletable, for example "dsep" or "possible_descendants_cpdag".
+/// @return Internal CIfly ruletable representation.
+/// @examples
+/// rt <- bundledRuletable("dsep")
+/// edgelist <- list("-->" = rbind(c(1, 2), c(3, 2), c(2, 4)))
+/// sets <- list("X" = c(1), "Z" = c(4))
+/// reach(edgelist, sets, rt)
+/// @export
+#[extendr]
+fn bundledRuletable(name: &str) -> Result<Ruletable> {
+    cifly::Ruletable::bundled(name)
+        .map(Ruletable)
+        .ok_or(extendr_api::Error::from(format!(
+            "Error: no bundled ruletable named {}, available are: {}",
+            name,
+            cifly::tables::NAMES.join(", ")
+        )))
+}
+
+/// Names of the ruletables bundled with CIfly.
+///
+/// Returns the names of all ruletables bundled with CIfly, which can be obtained with `bundledRuletable`.
+///
+/// @return A character vector of ruletable names.
+/// @examples
+/// bundledRuletableNames()
+/// @export
+#[extendr]
+fn bundledRuletableNames() -> Vec<String> {
+    cifly::tables::NAMES
+        .iter()
+        .map(|&name| name.to_owned())
+        .collect()
+}
+
+/// Inspect a CIfly ruletable.
+///
+/// Returns the declarations, start and output states and rules of a ruletable. Patterns such as "..." are expanded to the matched edge types and colors and the rule expressions are given in ruletable syntax with definitions expanded.
+///
+/// @param ruletable Path to a ruletable file.
+/// @param tableAsString Optional argument to enable passing the ruletable as multi-line string. Default value is FALSE.
+/// @return A list with the character vectors edges, reverseEdges, colors and sets, the lists starts with entries set, edge and color and outputs with entries edge and color, where color is NULL if the ruletable has no colors, and a list rules, each with entries prevEdges, prevColors, nextEdges, nextColors and expression.
+/// @examples
+/// info <- ruletableInfo(bundledRuletable("dsep"))
+/// info$edges
+/// info$rules[[1]]$expression
+/// @export
+#[extendr]
+fn ruletableInfo(ruletable: Robj, #[default = "FALSE"] tableAsString: bool) -> Result<Robj> {
+    let parsed_ruletable;
+    let ruletable = match <&Ruletable>::try_from(&ruletable) {
+        Ok(rt) => &rt.0,
+        Err(_) => {
+            parsed_ruletable = to_ruletable(
+                ruletable.as_str().ok_or(extendr_api::Error::from(
+                    "Error: expected a string as ruletable argument.",
+                ))?,
+                tableAsString,
+            )?;
+            &parsed_ruletable
+        }
+    };
+    let has_colors = !ruletable.color_names().is_empty();
+    let colors = |colors: Vec<Option<String>>| -> Robj {
+        if has_colors {
+            colors.into_iter().flatten().collect::<Vec<_>>().into()
+        } else {
+            ().into()
+        }
+    };
+
+    let edges = ruletable.edge_names();
+    let reverse_edges: Vec<_> = edges
+        .iter()
+        .map(|edge| ruletable.reverse_edge_name(edge).unwrap_or_default())
+        .collect();
+    let (starts, outputs) = (ruletable.start_specs(), ruletable.output_specs());
+    let set_names = ruletable.set_names();
+    let rules = List::from_values(ruletable.rule_specs().into_iter().map(|rule| {
+        list!(
+            prevEdges = rule.prev_edges,
+            prevColors = rule.prev_colors,
+            nextEdges = rule.next_edges,
+            nextColors = rule.next_colors,
+            expression = rule.expression.to_ruletable_string(&set_names)
+        )
+    }));
+    Ok(list!(
+        edges = edges,
+        reverseEdges = reverse_edges,
+        colors = ruletable.color_names(),
+        sets = ruletable.set_names(),
+        starts = list!(
+            set = starts.iter().map(|s| s.set.clone()).collect::<Vec<_>>(),
+            edge = starts.iter().map(|s| s.edge.clone()).collect::<Vec<_>>(),
+            color = colors(starts.iter().map(|s| s.color.clone()).collect())
+        ),
+        outputs = list!(
+            edge = outputs.iter().map(|o| o.edge.clone()).collect::<Vec<_>>(),
+            color = colors(outputs.iter().map(|o| o.color.clone()).collect())
+        ),
+        rules = rules
+    )
+    .into())
+}
+
 #[extendr]
 struct Graph(cifly::Graph);
 
@@ -229,6 +423,204 @@ fn parseSets(
     Ok(Sets(to_sets(&sets, ruletable_ref)?))
 }
 
+/// Check whether a set is a valid adjustment set in a CPDAG.
+///
+/// Checks whether W is a valid adjustment set relative to X and Y in a CPDAG following the generalized adjustment criterion. DAGs can be passed as CPDAGs without undirected edges.
+///
+/// @param graph A list mapping edge types "-->" and "---" to edge lists stored in matrix format.
+/// @param X A vector of treatment nodes.
+/// @param Y A vector of outcome nodes.
+/// @param W A vector of nodes in the candidate adjustment set.
+/// @return TRUE if W is a valid adjustment set and FALSE otherwise.
+/// @examples
+/// cpdag <- list("-->" = rbind(c(2, 3), c(3, 4)), "---" = rbind(c(1, 2)))
+/// isCpdagAdjustment(cpdag, c(3), c(4), c(2))
+/// @export
+#[extendr]
+fn isCpdagAdjustment(graph: Robj, X: Robj, Y: Robj, W: Robj) -> Result<bool> {
+    cifly::algorithms::is_cpdag_adjustment(
+        &to_edge_lists(&graph)?,
+        &to_node_ids(&X)?,
+        &to_node_ids(&Y)?,
+        &to_node_ids(&W)?,
+    )
+    .map_err(graph_error)
+}
+
+/// Construct the optimal adjustment set in a CPDAG.
+///
+/// Constructs the optimal adjustment set relative to X and Y in a CPDAG. DAGs can be passed as CPDAGs without undirected edges.
+///
+/// @param graph A list mapping edge types "-->" and "---" to edge lists stored in matrix format.
+/// @param X A vector of treatment nodes.
+/// @param Y A vector of outcome nodes.
+/// @return A vector of the nodes in the optimal adjustment set or NULL if no valid adjustment set exists.
+/// @examples
+/// cpdag <- list("-->" = rbind(c(2, 5), c(4, 5), c(3, 5)), "---" = rbind(c(1, 2), c(1, 4), c(2, 4)))
+/// optimalAdjustment(cpdag, c(3), c(5))
+/// @export
+#[extendr]
+fn optimalAdjustment(graph: Robj, X: Robj, Y: Robj) -> Result<Robj> {
+    cifly::algorithms::optimal_adjustment(
+        &to_edge_lists(&graph)?,
+        &to_node_ids(&X)?,
+        &to_node_ids(&Y)?,
+    )
+    .map(from_optional_node_ids)
+    .map_err(graph_error)
+}
+
+/// Compute the parent adjustment distance between two CPDAGs.
+///
+/// Counts the ordered pairs of nodes for which adjusting for the parents in the guessed CPDAG leads to a wrong conclusion about the causal effect in the true CPDAG.
+///
+/// @param p The number of nodes.
+/// @param gTrue A list mapping edge types "-->" and "---" to edge lists of the true graph.
+/// @param gGuess A list mapping edge types "-->" and "---" to edge lists of the guessed graph.
+/// @return The number of mistakes.
+/// @examples
+/// gTrue <- list("-->" = rbind(c(1, 2), c(2, 3), c(1, 4), c(4, 3)))
+/// gGuess <- list("-->" = rbind(c(1, 2), c(2, 3), c(4, 1), c(4, 3)))
+/// parentAid(4, gTrue, gGuess)
+/// @export
+#[extendr]
+fn parentAid(p: Robj, gTrue: Robj, gGuess: Robj) -> Result<usize> {
+    let num_nodes = match (p.as_integer(), p.as_real()) {
+        (Some(p), _) if p >= 0 => p as usize,
+        (_, Some(p)) if p >= 0.0 => p.round() as usize,
+        _ => {
+            return Err(extendr_api::Error::from(
+                "Error: expected non-negative integer as number of nodes",
+            ))
+        }
+    };
+    cifly::algorithms::parent_aid(num_nodes, &to_edge_lists(&gTrue)?, &to_edge_lists(&gGuess)?)
+        .map_err(graph_error)
+}
+
+/// Find a front-door adjustment set in a DAG.
+///
+/// Finds the largest front-door adjustment set Z relative to X and Y in a DAG such that Z contains I and is contained in R.
+///
+/// @param graph A list mapping edge type "-->" to an edge list stored in matrix format.
+/// @param X A vector of treatment nodes.
+/// @param Y A vector of outcome nodes.
+/// @param I A vector of nodes that have to be contained in the front-door adjustment set.
+/// @param R A vector of nodes that may be contained in the front-door adjustment set.
+/// @return A vector of the nodes in the front-door adjustment set or NULL if no such set exists.
+/// @examples
+/// dag <- list("-->" = rbind(c(1, 2), c(2, 3), c(4, 1), c(4, 3)))
+/// frontdoor(dag, c(1), c(3), c(), c(2))
+/// @export
+#[extendr]
+fn frontdoor(graph: Robj, X: Robj, Y: Robj, I: Robj, R: Robj) -> Result<Robj> {
+    cifly::algorithms::frontdoor(
+        &to_edge_lists(&graph)?,
+        &to_node_ids(&X)?,
+        &to_node_ids(&Y)?,
+        &to_node_ids(&I)?,
+        &to_node_ids(&R)?,
+    )
+    .map(from_optional_node_ids)
+    .map_err(graph_error)
+}
+
+/// Find the nearest d-separator in an ADMG.
+///
+/// Finds the d-separator Z of X and Y in an ADMG such that Z contains I and is contained in R that is nearest to X.
+///
+/// @param graph A list mapping edge types "-->" and "<->" to edge lists stored in matrix format.
+/// @param X A vector of nodes.
+/// @param Y A vector of nodes.
+/// @param I A vector of nodes that have to be contained in the separator.
+/// @param R A vector of nodes that may be contained in the separator.
+/// @return A vector of the nodes in the separator or NULL if no such separator exists.
+/// @examples
+/// admg <- list("-->" = rbind(c(2, 3), c(2, 4), c(3, 4)), "<->" = rbind(c(1, 3)))
+/// findNearestDsep(admg, c(1), c(4), c(), c(1, 2, 3, 4))
+/// @export
+#[extendr]
+fn findNearestDsep(graph: Robj, X: Robj, Y: Robj, I: Robj, R: Robj) -> Result<Robj> {
+    cifly::algorithms::find_nearest_dsep(
+        &to_edge_lists(&graph)?,
+        &to_node_ids(&X)?,
+        &to_node_ids(&Y)?,
+        &to_node_ids(&I)?,
+        &to_node_ids(&R)?,
+    )
+    .map(from_optional_node_ids)
+    .map_err(graph_error)
+}
+
+/// Find a minimal d-separator in an ADMG.
+///
+/// Finds a minimal d-separator Z of X and Y in an ADMG such that Z contains I and is contained in R.
+///
+/// @param graph A list mapping edge types "-->" and "<->" to edge lists stored in matrix format.
+/// @param X A vector of nodes.
+/// @param Y A vector of nodes.
+/// @param I A vector of nodes that have to be contained in the separator.
+/// @param R A vector of nodes that may be contained in the separator.
+/// @return A vector of the nodes in the separator or NULL if no such separator exists.
+/// @examples
+/// admg <- list("-->" = rbind(c(2, 1), c(2, 3), c(3, 5), c(4, 3), c(4, 5)), "<->" = rbind(c(1, 3)))
+/// findMinimalDsep(admg, c(1), c(5), c(), c(1, 2, 3, 4, 5))
+/// @export
+#[extendr]
+fn findMinimalDsep(graph: Robj, X: Robj, Y: Robj, I: Robj, R: Robj) -> Result<Robj> {
+    cifly::algorithms::find_minimal_dsep(
+        &to_edge_lists(&graph)?,
+        &to_node_ids(&X)?,
+        &to_node_ids(&Y)?,
+        &to_node_ids(&I)?,
+        &to_node_ids(&R)?,
+    )
+    .map(from_optional_node_ids)
+    .map_err(graph_error)
+}
+
+/// Find the optimal conditional instrument in an ADMG.
+///
+/// Finds the optimal conditional instrument for the effect of x on y in an ADMG. The instrument is only guaranteed to be optimal if there is no adjustment set relative to x and y.
+///
+/// @param graph A list mapping edge types "-->" and "<->" to edge lists stored in matrix format.
+/// @param x The treatment node.
+/// @param y The outcome node.
+/// @return A list with the vector of instruments z and the vector of conditioning nodes w or NULL if the criterion yields no instrument.
+/// @examples
+/// admg <- list("-->" = rbind(c(1, 4), c(2, 1), c(2, 3), c(3, 1), c(4, 5)), "<->" = rbind(c(3, 5), c(4, 5)))
+/// optimalInstrument(admg, 4, 5)
+/// @export
+#[extendr]
+fn optimalInstrument(graph: Robj, x: Robj, y: Robj) -> Result<Robj> {
+    cifly::algorithms::optimal_instrument(&to_edge_lists(&graph)?, to_node_id(&x)?, to_node_id(&y)?)
+        .map(from_optional_instrument)
+        .map_err(graph_error)
+}
+
+/// Find a conditional instrument in an ADMG.
+///
+/// Finds a conditional instrument consisting of a single instrument for the effect of x on y in an ADMG. If such an instrument exists, it is guaranteed to be found.
+///
+/// @param graph A list mapping edge types "-->" and "<->" to edge lists stored in matrix format.
+/// @param x The treatment node.
+/// @param y The outcome node.
+/// @return A list with the vector containing the instrument z and the vector of conditioning nodes w or NULL if no such instrument exists.
+/// @examples
+/// admg <- list("-->" = rbind(c(1, 2), c(1, 3), c(3, 4)), "<->" = rbind(c(2, 4), c(3, 4)))
+/// soundAndCompleteInstrument(admg, 3, 4)
+/// @export
+#[extendr]
+fn soundAndCompleteInstrument(graph: Robj, x: Robj, y: Robj) -> Result<Robj> {
+    cifly::algorithms::sound_and_complete_instrument(
+        &to_edge_lists(&graph)?,
+        to_node_id(&x)?,
+        to_node_id(&y)?,
+    )
+    .map(from_optional_instrument)
+    .map_err(graph_error)
+}
+
 fn to_ruletable(ruletable_str: &str, as_string: bool) -> Result<cifly::Ruletable> {
     let ruletable_res = if as_string {
         cifly::Ruletable::from_multiline_string(ruletable_str)
@@ -245,6 +637,10 @@ fn to_ruletable(ruletable_str: &str, as_string: bool) -> Result<cifly::Ruletable
 }
 
 fn to_graph(graph: &Robj, ruletable: &cifly::Ruletable) -> Result<cifly::Graph> {
+    cifly::Graph::new(&to_edge_lists(graph)?, ruletable).map_err(graph_error)
+}
+
+fn to_edge_lists(graph: &Robj) -> Result<HashMap<String, Vec<(usize, usize)>>> {
     let vecs = graph
         .as_list()
         .ok_or(extendr_api::Error::from(
@@ -305,8 +701,11 @@ fn to_graph(graph: &Robj, ruletable: &cifly::Ruletable) -> Result<cifly::Graph>
         };
         edge_lists.insert(edge_string.to_string(), edge_list);
     }
-    cifly::Graph::new(&edge_lists, ruletable)
-        .map_err(|err| extendr_api::Error::from(format!("Error: could not read graph: {}", err)))
+    Ok(edge_lists)
+}
+
+fn graph_error(err: cifly::ParseGraphError) -> extendr_api::Error {
+    extendr_api::Error::from(format!("Error: could not read graph: {}", err))
 }
 
 fn to_sets(sets: &Robj, ruletable: &cifly::Ruletable) -> Result<cifly::Sets> {
@@ -318,41 +717,66 @@ fn to_sets(sets: &Robj, ruletable: &cifly::Ruletable) -> Result<cifly::Sets> {
         .into_hashmap();
     let mut set_lists = HashMap::new();
     for (set_string, v) in vecs.iter() {
-        if !v.is_vector() && !v.is_number() && !v.is_null() {
-            return Err(extendr_api::Error::from(
-                "Error: each set should be a vector",
-            ));
-        }
-        let s;
-        if v.is_null() {
-            s = Vec::new();
-        } else if v.is_integer() {
-            s = v
-                .as_integer_slice()
-                .ok_or(extendr_api::Error::from(
-                    "Error: each should be given as vector",
-                ))?
-                .iter()
-                .map(|&x| i32_to_node_id(x))
-                .collect::<Result<Vec<_>>>()?;
-        } else if v.is_real() {
-            s = v
-                .as_real_slice()
-                .ok_or(extendr_api::Error::from(
-                    "Error: each should be given as vector",
-                ))?
-                .iter()
-                .map(|&x| f64_to_node_id(x))
-                .collect::<Result<Vec<_>>>()?;
-        } else {
-            panic!("Error: set vector contains neither integers nor floating point numbers");
-        }
-        set_lists.insert(set_string.to_string(), s);
+        set_lists.insert(set_string.to_string(), to_node_ids(v)?);
     }
     cifly::Sets::new(&set_lists, ruletable)
         .map_err(|err| extendr_api::Error::from(format!("Error: could not read sets: {}", err)))
 }
 
+fn to_node_ids(v: &Robj) -> Result<Vec<usize>> {
+    if !v.is_vector() && !v.is_number() && !v.is_null() {
+        return Err(extendr_api::Error::from(
+            "Error: each set should be a vector",
+        ));
+    }
+    if v.is_null() {
+        Ok(Vec::new())
+    } else if v.is_integer() {
+        v.as_integer_slice()
+            .ok_or(extendr_api::Error::from(
+                "Error: each should be given as vector",
+            ))?
+            .iter()
+            .map(|&x| i32_to_node_id(x))
+            .collect()
+    } else if v.is_real() {
+        v.as_real_slice()
+            .ok_or(extendr_api::Error::from(
+                "Error: each should be given as vector",
+            ))?
+            .iter()
+            .map(|&x| f64_to_node_id(x))
+            .collect()
+    } else {
+        panic!("Error: set vector contains neither integers nor floating point numbers");
+    }
+}
+
+fn to_node_id(v: &Robj) -> Result<usize> {
+    match to_node_ids(v)?.as_slice() {
+        &[id] => Ok(id),
+        _ => Err(extendr_api::Error::from("Error: expected a single node id")),
+    }
+}
+
+fn from_node_ids(ids: Vec<usize>) -> Vec<usize> {
+    ids.into_iter().map(|x| x + 1).collect()
+}
+
+fn from_optional_node_ids(ids: Option<Vec<usize>>) -> Robj {
+    match ids {
+        Some(ids) => from_node_ids(ids).into(),
+        None => ().into(),
+    }
+}
+
+fn from_optional_instrument(instrument: Option<cifly::algorithms::Instrument>) -> Robj {
+    match instrument {
+        Some((z, w)) => list!(z = from_node_ids(z), w = from_node_ids(w)).into(),
+        None => ().into(),
+    }
+}
+
 fn i32_to_node_id(id: i32) -> Result<usize> {
     if id >= 1 {
         Ok((id as usize) - 1)
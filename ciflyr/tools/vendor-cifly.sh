@@ -0,0 +1,34 @@
+#!/usr/bin/env bash
+# Replaces the cifly crate in src/rust/vendor.tar.xz by the sources in ../cifly,
+# which the R package is built against. With --check, only verifies that the
+# vendored copy is identical to ../cifly and fails otherwise, for use in CI.
+set -euo pipefail
+
+root="$(cd "$(dirname "$0")/../.." && pwd)"
+tarball="$root/ciflyr/src/rust/vendor.tar.xz"
+tmp="$(mktemp -d)"
+trap 'rm -rf "$tmp"' EXIT
+
+# the ruletables directory of cifly is a symlink, so copy its targets
+mkdir "$tmp/cifly"
+cp -rL "$root/cifly/"{Cargo.toml,README.md,CHANGELOG.md,src,ruletables} "$tmp/cifly/"
+tar xJf "$tarball" -C "$tmp"
+
+if [ "${1:-}" = "--check" ]; then
+    if ! diff -r --exclude=.cargo-checksum.json "$tmp/cifly" "$tmp/vendor/cifly"; then
+        echo "vendored cifly differs from cifly/, run ciflyr/tools/vendor-cifly.sh" >&2
+        exit 1
+    fi
+    exit 0
+fi
+
+rm -rf "$tmp/vendor/cifly"
+mv "$tmp/cifly" "$tmp/vendor/cifly"
+(
+    cd "$tmp/vendor/cifly"
+    find . -type f | sed 's|^\./||' | LC_ALL=C sort | while read -r file; do
+        printf '"%s": "%s"\n' "$file" "$(sha256sum "$file" | cut -d ' ' -f 1)"
+    done | paste -sd, - | sed 's/^/{"files": {/; s/$/}, "package": null}/' > .cargo-checksum.json
+)
+tar cJf "$tarball" -C "$tmp" --sort=name --owner=0 --group=0 --numeric-owner \
+    --mtime='2026-01-01' vendor